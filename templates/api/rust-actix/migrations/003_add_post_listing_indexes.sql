@@ -0,0 +1,20 @@
+-- Support filtering and sorting on post listings
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Keyset pagination compares (created_at, id) and (updated_at, id) row
+-- values, which leave out rows with NULL timestamps
+UPDATE posts SET created_at = NOW() WHERE created_at IS NULL;
+UPDATE posts SET updated_at = NOW() WHERE updated_at IS NULL;
+ALTER TABLE posts
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN updated_at SET NOT NULL;
+
+-- Keyset pagination per sort order
+CREATE INDEX idx_posts_updated_at_id ON posts(updated_at DESC, id DESC);
+CREATE INDEX idx_posts_title_id ON posts(title, id);
+
+-- Author filter combined with the default sort
+CREATE INDEX idx_posts_user_id_created_at ON posts(user_id, created_at DESC, id DESC);
+
+-- Title substring (ILIKE) filter
+CREATE INDEX idx_posts_title_trgm ON posts USING GIN (title gin_trgm_ops);
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Full-text search over post titles (weight A) and content (weight B).
+-- The text search configuration must match SEARCH_LANGUAGE, which is checked at startup.
+ALTER TABLE posts
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A') ||
+        setweight(to_tsvector('english', content), 'B')
+    ) STORED;
+
+CREATE INDEX idx_posts_search_vector ON posts USING GIN (search_vector);
\ No newline at end of file
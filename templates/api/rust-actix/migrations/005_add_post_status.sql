@@ -0,0 +1,16 @@
+-- Draft, scheduled and published post states
+CREATE TYPE post_status AS ENUM ('draft', 'scheduled', 'published');
+
+-- Existing posts were public, so they start out published
+ALTER TABLE posts
+    ADD COLUMN status post_status NOT NULL DEFAULT 'published',
+    ADD COLUMN publish_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE posts SET publish_at = created_at;
+
+ALTER TABLE posts
+    ADD CONSTRAINT chk_posts_scheduled_publish_at
+    CHECK (status <> 'scheduled' OR publish_at IS NOT NULL);
+
+-- Lookup of scheduled posts that are due
+CREATE INDEX idx_posts_scheduled_publish_at ON posts(publish_at) WHERE status = 'scheduled';
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- Soft deletion: trashed posts keep their row until purged
+ALTER TABLE posts ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Trash listing per owner and retention purge
+CREATE INDEX idx_posts_trash ON posts(user_id, deleted_at DESC, id DESC) WHERE deleted_at IS NOT NULL;
\ No newline at end of file
@@ -0,0 +1,16 @@
+-- Create tags table; names are stored normalized (lowercase, hyphenated)
+CREATE TABLE tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create post_tags join table
+CREATE TABLE post_tags (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+-- Posts by tag, for filtering and usage counts
+CREATE INDEX idx_post_tags_tag_id ON post_tags(tag_id);
\ No newline at end of file
@@ -0,0 +1,33 @@
+-- Content format and the HTML, excerpt and reading time rendered from it on write
+CREATE TYPE content_format AS ENUM ('plain', 'markdown');
+
+ALTER TABLE posts
+    ADD COLUMN content_format content_format NOT NULL DEFAULT 'plain',
+    ADD COLUMN content_html TEXT NOT NULL DEFAULT '',
+    ADD COLUMN excerpt TEXT NOT NULL DEFAULT '',
+    ADD COLUMN reading_time_minutes INTEGER NOT NULL DEFAULT 1;
+
+-- Revisions keep the format so that rollbacks render the same way
+ALTER TABLE post_revisions
+    ADD COLUMN content_format content_format NOT NULL DEFAULT 'plain';
+
+-- Existing posts are plain text; render them the way the server does:
+-- escaped paragraphs with line breaks, and a 200 character excerpt
+UPDATE posts SET
+    content_html = (
+        SELECT COALESCE(string_agg('<p>' || replace(btrim(paragraph, E' \t\r\n'), E'\n', '<br>') || '</p>', '' ORDER BY position), '')
+        FROM unnest(string_to_array(
+            replace(replace(replace(replace(replace(btrim(content, E' \t\r\n'),
+                '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '"', '&quot;'), '''', '&#39;'),
+            E'\n\n'
+        )) WITH ORDINALITY AS paragraphs(paragraph, position)
+        WHERE btrim(paragraph, E' \t\r\n') <> ''
+    ),
+    excerpt = CASE
+        WHEN char_length(btrim(regexp_replace(content, '\s+', ' ', 'g'))) <= 200
+            THEN btrim(regexp_replace(content, '\s+', ' ', 'g'))
+        ELSE regexp_replace(left(btrim(regexp_replace(content, '\s+', ' ', 'g')), 201), '\s*\S*$', '') || '…'
+    END,
+    reading_time_minutes = GREATEST(1, CEIL(
+        COALESCE(array_length(regexp_split_to_array(btrim(content), '\s+'), 1), 0) / 200.0
+    ));
\ No newline at end of file
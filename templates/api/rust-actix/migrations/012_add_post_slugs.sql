@@ -0,0 +1,28 @@
+-- Current slug of each post; custom_slug marks slugs chosen by the author,
+-- which are kept when the title changes
+ALTER TABLE posts
+    ADD COLUMN slug VARCHAR(255),
+    ADD COLUMN custom_slug BOOLEAN NOT NULL DEFAULT false;
+
+-- Every slug a post has had, so old links can redirect to the current one.
+-- Deferred so that a new post can claim its slug before it is inserted.
+CREATE TABLE post_slugs (
+    slug VARCHAR(255) PRIMARY KEY,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_post_slugs_post_id ON post_slugs(post_id);
+
+-- Existing posts get an ASCII slug from their title plus a short id suffix,
+-- which keeps them unique without transliteration
+UPDATE posts SET slug = COALESCE(
+    NULLIF(trim(BOTH '-' FROM left(trim(BOTH '-' FROM regexp_replace(lower(title), '[^a-z0-9]+', '-', 'g')), 71)), ''),
+    'post'
+) || '-' || left(id::text, 8);
+
+INSERT INTO post_slugs (slug, post_id) SELECT slug, id FROM posts;
+
+ALTER TABLE posts
+    ALTER COLUMN slug SET NOT NULL,
+    ADD CONSTRAINT posts_slug_key UNIQUE (slug);
\ No newline at end of file
@@ -0,0 +1,29 @@
+-- Stored files, keyed by the SHA-256 of their content so identical uploads
+-- share one object in storage
+CREATE TABLE attachment_blobs (
+    sha256 CHAR(64) PRIMARY KEY,
+    storage_key VARCHAR(255) NOT NULL,
+    thumbnail_key VARCHAR(255),
+    content_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    width INTEGER,
+    height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Bumped on every upload of the blob; unreferenced blobs are only swept
+    -- once this is old enough that no upload can still be linking to them
+    last_used_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create attachments table
+CREATE TABLE attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    sha256 CHAR(64) NOT NULL REFERENCES attachment_blobs(sha256),
+    filename VARCHAR(255) NOT NULL,
+    private BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_attachments_post_id ON attachments(post_id, created_at);
+CREATE INDEX idx_attachments_sha256 ON attachments(sha256);
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- In-progress tus uploads. The received bytes live in a file named after the
+-- upload id; upload_offset only counts bytes that reached the disk.
+CREATE TABLE resumable_uploads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    declared_type VARCHAR(255),
+    private BOOLEAN NOT NULL DEFAULT false,
+    -- Upload-Metadata as sent, echoed back on HEAD
+    metadata TEXT NOT NULL DEFAULT '',
+    upload_length BIGINT NOT NULL,
+    upload_offset BIGINT NOT NULL DEFAULT 0,
+    -- Set once the file has been handed to attachment storage
+    attachment_id UUID REFERENCES attachments(id) ON DELETE SET NULL,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_resumable_uploads_expires_at ON resumable_uploads(expires_at);
\ No newline at end of file
@@ -0,0 +1,16 @@
+-- Users a post's author shared it with. Editors may change the post,
+-- viewers only read it while it is unpublished.
+CREATE TYPE collaborator_role AS ENUM ('editor', 'viewer');
+
+CREATE TABLE post_collaborators (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role collaborator_role NOT NULL,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id)
+);
+
+-- "Shared with me" listing
+CREATE INDEX idx_post_collaborators_user_id ON post_collaborators(user_id);
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Daily view totals per post. Views are buffered and deduplicated in
+-- memory, then added here in batches.
+CREATE TABLE post_view_daily (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    views BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (post_id, day)
+);
\ No newline at end of file
@@ -0,0 +1,50 @@
+-- Moderators are designated directly in the database, e.g.
+-- UPDATE users SET is_moderator = TRUE WHERE email = '...';
+ALTER TABLE users ADD COLUMN is_moderator BOOLEAN NOT NULL DEFAULT FALSE;
+-- Suspended users can neither sign in nor use their existing tokens
+ALTER TABLE users ADD COLUMN suspended_at TIMESTAMP WITH TIME ZONE;
+
+-- Hidden posts are only visible to their author and collaborators.
+-- hidden_by is NULL when the post was hidden by the report threshold.
+ALTER TABLE posts ADD COLUMN hidden_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE posts ADD COLUMN hidden_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE TYPE report_reason AS ENUM (
+    'spam', 'harassment', 'hate_speech', 'violence', 'sexual_content', 'misinformation', 'other'
+);
+CREATE TYPE report_status AS ENUM ('open', 'dismissed', 'actioned');
+
+CREATE TABLE reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    reporter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason report_reason NOT NULL,
+    details TEXT,
+    status report_status NOT NULL DEFAULT 'open',
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- One open report per user and post; counted against the hide threshold
+CREATE UNIQUE INDEX idx_reports_open_reporter ON reports(post_id, reporter_id) WHERE status = 'open';
+-- Moderation queue, oldest first
+CREATE INDEX idx_reports_status_created_at ON reports(status, created_at, id);
+
+CREATE TYPE moderation_action AS ENUM ('dismiss', 'hide_post', 'suspend_author');
+
+-- Audit log of moderation decisions. moderator_id is NULL for automatic
+-- hiding by the report threshold.
+CREATE TABLE moderation_actions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID REFERENCES posts(id) ON DELETE SET NULL,
+    report_id UUID REFERENCES reports(id) ON DELETE SET NULL,
+    target_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    moderator_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action moderation_action NOT NULL,
+    note TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_moderation_actions_created_at ON moderation_actions(created_at, id);
+CREATE INDEX idx_moderation_actions_post_id ON moderation_actions(post_id);
\ No newline at end of file
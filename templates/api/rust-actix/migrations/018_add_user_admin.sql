@@ -0,0 +1,3 @@
+-- Admins can export and import posts on behalf of every user. Like
+-- moderators they are designated directly in the database.
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
@@ -0,0 +1,22 @@
+-- Private folders a user sorts their bookmarks into
+CREATE TABLE bookmark_folders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+-- Posts saved to read later. Deleting a folder leaves its bookmarks unfiled.
+CREATE TABLE bookmarks (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    folder_id UUID REFERENCES bookmark_folders(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, post_id)
+);
+
+-- Bookmark listing, most recently saved first
+CREATE INDEX idx_bookmarks_user_created_at ON bookmarks(user_id, created_at DESC, post_id DESC);
+CREATE INDEX idx_bookmarks_folder_id ON bookmarks(folder_id);
\ No newline at end of file
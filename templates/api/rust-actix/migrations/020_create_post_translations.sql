@@ -0,0 +1,18 @@
+-- Language of a post's own title and content, its canonical locale.
+-- Existing posts are taken to be English.
+ALTER TABLE posts ADD COLUMN locale VARCHAR(35) NOT NULL DEFAULT 'en';
+
+-- Title and content of a post in further locales, rendered in the post's
+-- content format
+CREATE TABLE post_translations (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    locale VARCHAR(35) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    content_html TEXT NOT NULL,
+    excerpt TEXT NOT NULL,
+    reading_time_minutes INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, locale)
+);
\ No newline at end of file
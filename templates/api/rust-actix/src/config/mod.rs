@@ -1,4 +1,6 @@
-use std::env;
+use std::{env, num::NonZeroU64};
+
+use crate::models::normalize_locale;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -6,6 +8,46 @@ pub struct Config {
     pub jwt_secret: String,
     pub server_host: String,
     pub server_port: u16,
+    pub default_page_size: i64,
+    pub max_page_size: i64,
+    pub search_language: String,
+    pub default_locale: String,
+    pub publish_interval_secs: u64,
+    pub trash_retention_days: i32,
+    pub trash_purge_interval_secs: u64,
+    pub require_if_match: bool,
+    pub cache_control_post: String,
+    pub cache_control_post_list: String,
+    pub cache_control_post_search: String,
+    pub batch_max_operations: usize,
+    pub export_batch_size: i64,
+    pub import_batch_size: usize,
+    pub feed_title: String,
+    pub feed_size: i64,
+    pub public_base_url: Option<String>,
+    pub comment_max_depth: i32,
+    pub reaction_kinds: Vec<String>,
+    pub report_hide_threshold: i64,
+    pub storage_backend: String,
+    pub storage_local_path: String,
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    pub s3_force_path_style: bool,
+    pub upload_max_bytes: usize,
+    pub upload_allowed_types: Vec<String>,
+    pub thumbnail_size: u32,
+    pub attachment_signing_key: String,
+    pub attachment_url_ttl_secs: u64,
+    pub blob_sweep_interval_secs: u64,
+    pub resumable_upload_path: String,
+    pub resumable_upload_expiration_secs: i64,
+    pub resumable_upload_sweep_interval_secs: u64,
+    pub view_flush_interval_secs: u64,
+    pub view_dedup_window_secs: u64,
+    pub trust_proxy_headers: bool,
 }
 
 impl Config {
@@ -21,6 +63,147 @@ impl Config {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .expect("SERVER_PORT must be a valid number"),
+            default_page_size: env::var("DEFAULT_PAGE_SIZE")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("DEFAULT_PAGE_SIZE must be a valid number"),
+            max_page_size: env::var("MAX_PAGE_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .expect("MAX_PAGE_SIZE must be a valid number"),
+            search_language: env::var("SEARCH_LANGUAGE")
+                .unwrap_or_else(|_| "english".to_string()),
+            default_locale: normalize_locale(
+                &env::var("DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string()),
+            )
+            .expect("DEFAULT_LOCALE must be a valid language tag"),
+            publish_interval_secs: env::var("PUBLISH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<NonZeroU64>()
+                .expect("PUBLISH_INTERVAL_SECS must be a positive number")
+                .get(),
+            trash_retention_days: env::var("TRASH_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .ok()
+                .filter(|days: &i32| *days >= 0)
+                .expect("TRASH_RETENTION_DAYS must be a non-negative number"),
+            trash_purge_interval_secs: env::var("TRASH_PURGE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse::<NonZeroU64>()
+                .expect("TRASH_PURGE_INTERVAL_SECS must be a positive number")
+                .get(),
+            require_if_match: env::var("REQUIRE_IF_MATCH")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("REQUIRE_IF_MATCH must be true or false"),
+            cache_control_post: env::var("CACHE_CONTROL_POST")
+                .unwrap_or_else(|_| "no-cache".to_string()),
+            cache_control_post_list: env::var("CACHE_CONTROL_POST_LIST")
+                .unwrap_or_else(|_| "no-cache".to_string()),
+            cache_control_post_search: env::var("CACHE_CONTROL_POST_SEARCH")
+                .unwrap_or_else(|_| "no-cache".to_string()),
+            batch_max_operations: env::var("BATCH_MAX_OPERATIONS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .expect("BATCH_MAX_OPERATIONS must be a valid number"),
+            export_batch_size: env::var("EXPORT_BATCH_SIZE")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .expect("EXPORT_BATCH_SIZE must be a valid number"),
+            import_batch_size: env::var("IMPORT_BATCH_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .expect("IMPORT_BATCH_SIZE must be a valid number"),
+            feed_title: env::var("FEED_TITLE")
+                .unwrap_or_else(|_| "Posts".to_string()),
+            feed_size: env::var("FEED_SIZE")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("FEED_SIZE must be a valid number"),
+            public_base_url: env::var("PUBLIC_BASE_URL")
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty()),
+            comment_max_depth: env::var("COMMENT_MAX_DEPTH")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .expect("COMMENT_MAX_DEPTH must be a valid number"),
+            reaction_kinds: env::var("REACTION_KINDS")
+                .unwrap_or_else(|_| "like,love,laugh,insightful".to_string())
+                .split(',')
+                .map(|kind| kind.trim().to_string())
+                .filter(|kind| !kind.is_empty())
+                .collect(),
+            report_hide_threshold: env::var("REPORT_HIDE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .expect("REPORT_HIDE_THRESHOLD must be a valid number"),
+            storage_backend: env::var("STORAGE_BACKEND")
+                .unwrap_or_else(|_| "local".to_string()),
+            storage_local_path: env::var("STORAGE_LOCAL_PATH")
+                .unwrap_or_else(|_| "./uploads".to_string()),
+            s3_bucket: env::var("S3_BUCKET").ok(),
+            s3_region: env::var("S3_REGION")
+                .unwrap_or_else(|_| "us-east-1".to_string()),
+            s3_endpoint: env::var("S3_ENDPOINT").ok(),
+            s3_access_key_id: env::var("S3_ACCESS_KEY_ID").ok(),
+            s3_secret_access_key: env::var("S3_SECRET_ACCESS_KEY").ok(),
+            s3_force_path_style: env::var("S3_FORCE_PATH_STYLE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("S3_FORCE_PATH_STYLE must be true or false"),
+            upload_max_bytes: env::var("UPLOAD_MAX_BYTES")
+                .unwrap_or_else(|_| "10485760".to_string())
+                .parse()
+                .expect("UPLOAD_MAX_BYTES must be a valid number"),
+            upload_allowed_types: env::var("UPLOAD_ALLOWED_TYPES")
+                .unwrap_or_else(|_| {
+                    "image/jpeg,image/png,image/gif,image/webp,application/pdf,text/plain".to_string()
+                })
+                .split(',')
+                .map(|content_type| content_type.trim().to_string())
+                .filter(|content_type| !content_type.is_empty())
+                .collect(),
+            thumbnail_size: env::var("THUMBNAIL_SIZE")
+                .unwrap_or_else(|_| "320".to_string())
+                .parse()
+                .expect("THUMBNAIL_SIZE must be a valid number"),
+            attachment_signing_key: env::var("ATTACHMENT_SIGNING_KEY")
+                .unwrap_or_else(|_| "default-signing-key-change-in-production".to_string()),
+            attachment_url_ttl_secs: env::var("ATTACHMENT_URL_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("ATTACHMENT_URL_TTL_SECS must be a valid number"),
+            blob_sweep_interval_secs: env::var("BLOB_SWEEP_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse::<NonZeroU64>()
+                .expect("BLOB_SWEEP_INTERVAL_SECS must be a positive number")
+                .get(),
+            resumable_upload_path: env::var("RESUMABLE_UPLOAD_PATH")
+                .unwrap_or_else(|_| "./resumable-uploads".to_string()),
+            resumable_upload_expiration_secs: env::var("RESUMABLE_UPLOAD_EXPIRATION_SECS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .expect("RESUMABLE_UPLOAD_EXPIRATION_SECS must be a valid number"),
+            resumable_upload_sweep_interval_secs: env::var("RESUMABLE_UPLOAD_SWEEP_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse::<NonZeroU64>()
+                .expect("RESUMABLE_UPLOAD_SWEEP_INTERVAL_SECS must be a positive number")
+                .get(),
+            view_flush_interval_secs: env::var("VIEW_FLUSH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse::<NonZeroU64>()
+                .expect("VIEW_FLUSH_INTERVAL_SECS must be a positive number")
+                .get(),
+            view_dedup_window_secs: env::var("VIEW_DEDUP_WINDOW_SECS")
+                .unwrap_or_else(|_| "1800".to_string())
+                .parse()
+                .expect("VIEW_DEDUP_WINDOW_SECS must be a valid number"),
+            trust_proxy_headers: env::var("TRUST_PROXY_HEADERS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("TRUST_PROXY_HEADERS must be true or false"),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,523 @@
+use actix_multipart::{Field, Multipart};
+use actix_web::{
+    dev::HttpServiceFactory,
+    http::header::{
+        EntityTag, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_SECURITY_POLICY, LOCATION,
+        X_CONTENT_TYPE_OPTIONS,
+    },
+    web, HttpMessage, HttpRequest, HttpResponse, Result,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::Utc;
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::{viewer_id, AuthMiddleware},
+    models::{Attachment, AttachmentVariant, DownloadQuery, PostStatus, PostWithUser},
+    services::{
+        process_upload, storage_key, AttachmentService, DownloadSigner, PostService, UploadError,
+        THUMBNAIL_CONTENT_TYPE,
+    },
+    storage::{inline_disposition, StorageBackend},
+};
+
+use super::conditional::CacheValidators;
+
+/// Longest filename kept from an upload, in characters.
+const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Attachment routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::scope("/{id}/attachments")
+        .route("", web::get().to(get_attachments))
+        .service(
+            web::scope("")
+                .wrap(AuthMiddleware)
+                .route("", web::post().to(upload_attachment))
+        )
+}
+
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/attachments")
+        .route("/{id}/content", web::get().to(download_content))
+        .route("/{id}/thumbnail", web::get().to(download_thumbnail))
+        .service(
+            web::scope("")
+                .wrap(AuthMiddleware)
+                .route("/{id}", web::delete().to(delete_attachment))
+        )
+}
+
+/// A received file, ready to be attached to a post.
+pub(super) struct UploadedFile {
+    pub filename: String,
+    pub declared_type: Option<String>,
+    pub data: Bytes,
+    pub private: bool,
+}
+
+pub async fn upload_attachment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: Multipart,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    // Check ownership before reading the body
+    let post = match find_own_post(pool, post_id, user_id).await {
+        Ok(post) => post,
+        Err(response) => return Ok(response),
+    };
+
+    let file = match read_upload_form(payload, config.upload_max_bytes).await {
+        Ok(file) => file,
+        Err(response) => return Ok(response),
+    };
+
+    match store_attachment(pool, storage.get_ref(), &config, post_id, user_id, file).await {
+        Ok(attachment) => {
+            let signer = DownloadSigner::new(&config.attachment_signing_key, config.attachment_url_ttl_secs);
+            let signed = needs_signed_url(&attachment, &post);
+            Ok(HttpResponse::Created().json(signer.to_response(attachment, signed)))
+        }
+        Err(response) => Ok(response),
+    }
+}
+
+/// Checks an uploaded file against the upload rules, stores it and attaches
+/// it to a post.
+pub(super) async fn store_attachment(
+    pool: &PgPool,
+    storage: &dyn StorageBackend,
+    config: &Config,
+    post_id: Uuid,
+    user_id: Uuid,
+    file: UploadedFile,
+) -> Result<Attachment, HttpResponse> {
+    let data = file.data.clone();
+    let declared_type = file.declared_type.clone();
+    let allowed_types = config.upload_allowed_types.clone();
+    let thumbnail_size = config.thumbnail_size;
+    let processed = web::block(move || {
+        process_upload(&data, declared_type.as_deref(), &allowed_types, thumbnail_size)
+    })
+    .await;
+
+    let processed = match processed {
+        Ok(Ok(processed)) => processed,
+        Ok(Err(e @ UploadError::DisallowedType(_))) => {
+            return Err(HttpResponse::UnsupportedMediaType().json(serde_json::json!({
+                "error": e.to_string(),
+                "allowed": config.upload_allowed_types
+            })));
+        }
+        Ok(Err(e @ UploadError::InvalidImage(_))) => {
+            return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })));
+        }
+        Err(e) => {
+            log::error!("Failed to process upload: {:?}", e);
+            return Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to process upload"
+            })));
+        }
+    };
+
+    AttachmentService::new(pool, storage)
+        .create(post_id, user_id, &file.filename, file.private, file.data, processed)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to store attachment: {:?}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to store attachment"
+            }))
+        })
+}
+
+pub async fn get_attachments(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+    let viewer = viewer_id(&req).await;
+
+    let post = match find_post(pool, post_id).await {
+        Ok(post) if post.is_visible_to(viewer) => post,
+        Ok(_) => return Ok(post_not_found()),
+        Err(response) => return Ok(response),
+    };
+
+    // Private attachments are only listed for the post's author
+    let include_private = viewer == Some(post.user_id);
+
+    match AttachmentService::new(pool, storage.get_ref())
+        .list_for_post(post_id, include_private)
+        .await
+    {
+        Ok(attachments) => {
+            let signer = DownloadSigner::new(&config.attachment_signing_key, config.attachment_url_ttl_secs);
+            let attachments: Vec<_> = attachments
+                .into_iter()
+                .map(|attachment| {
+                    let signed = needs_signed_url(&attachment, &post);
+                    signer.to_response(attachment, signed)
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(attachments))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch attachments"
+            })))
+        }
+    }
+}
+
+pub async fn download_content(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<DownloadQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+) -> Result<HttpResponse> {
+    download(req, path.into_inner(), query.into_inner(), db, config, storage, AttachmentVariant::Original).await
+}
+
+pub async fn download_thumbnail(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<DownloadQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+) -> Result<HttpResponse> {
+    download(req, path.into_inner(), query.into_inner(), db, config, storage, AttachmentVariant::Thumbnail).await
+}
+
+async fn download(
+    req: HttpRequest,
+    attachment_id: Uuid,
+    query: DownloadQuery,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+    variant: AttachmentVariant,
+) -> Result<HttpResponse> {
+    let pool = db.get_pool();
+    let service = AttachmentService::new(pool, storage.get_ref());
+
+    let attachment = match service.find(attachment_id).await {
+        Ok(Some(attachment)) => attachment,
+        Ok(None) => return Ok(attachment_not_found()),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    let post = match find_post(pool, attachment.post_id).await {
+        Ok(post) => post,
+        Err(response) => return Ok(response),
+    };
+
+    // A valid signed URL always works; without one, public files are as
+    // visible as their post and private files not at all
+    let signer = DownloadSigner::new(&config.attachment_signing_key, config.attachment_url_ttl_secs);
+    let viewer = viewer_id(&req).await;
+    let valid_for = match signer.verify(attachment.id, variant, &query) {
+        Some(expires) => expires - Utc::now(),
+        None if attachment.private || query.signature.is_some() => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Download link is invalid or has expired"
+            })));
+        }
+        None if post.is_visible_to(viewer) => signer.ttl(),
+        None => return Ok(attachment_not_found()),
+    };
+
+    let Some(key) = storage_key(&attachment, variant) else {
+        return Ok(attachment_not_found());
+    };
+
+    // Backends that can serve files themselves get the download directly
+    match storage
+        .presigned_url(key, &attachment.filename, valid_for.to_std().unwrap_or_default())
+        .await
+    {
+        Ok(Some(url)) => {
+            return Ok(HttpResponse::Found()
+                .insert_header((LOCATION, url))
+                .insert_header((CACHE_CONTROL, "no-store"))
+                .finish());
+        }
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("Failed to presign attachment download: {:?}", e);
+            return Ok(storage_error());
+        }
+    }
+
+    // Stored files never change, so their hash identifies them
+    let etag = match variant {
+        AttachmentVariant::Original => EntityTag::new_strong(attachment.sha256.clone()),
+        AttachmentVariant::Thumbnail => EntityTag::new_strong(format!("{}-thumbnail", attachment.sha256)),
+    };
+    let validators = CacheValidators::new(etag, Some(attachment.created_at));
+    let cache_control = format!("private, max-age={}", valid_for.num_seconds().max(0));
+    if validators.is_fresh(&req) {
+        return Ok(validators.not_modified(&cache_control));
+    }
+
+    let data = match storage.get(key).await {
+        Ok(data) => data,
+        Err(e) => {
+            log::error!("Failed to read attachment {}: {:?}", attachment.id, e);
+            return Ok(storage_error());
+        }
+    };
+
+    let content_type = match variant {
+        AttachmentVariant::Original => attachment.content_type.as_str(),
+        AttachmentVariant::Thumbnail => THUMBNAIL_CONTENT_TYPE,
+    };
+
+    let mut response = HttpResponse::Ok();
+    validators.apply(&mut response, &cache_control);
+    Ok(response
+        .content_type(content_type)
+        .insert_header((CONTENT_DISPOSITION, inline_disposition(&attachment.filename)))
+        // Uploaded files must not run scripts in the API's origin
+        .insert_header((CONTENT_SECURITY_POLICY, "default-src 'none'; sandbox"))
+        .insert_header((X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .body(data))
+}
+
+pub async fn delete_attachment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    storage: web::Data<dyn StorageBackend>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let attachment_id = path.into_inner();
+    let pool = db.get_pool();
+    let service = AttachmentService::new(pool, storage.get_ref());
+
+    let attachment = match service.find(attachment_id).await {
+        Ok(Some(attachment)) => attachment,
+        Ok(None) => return Ok(attachment_not_found()),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    match find_post(pool, attachment.post_id).await {
+        Ok(post) if post.user_id == user_id => {}
+        Ok(post) if !post.is_visible_to(Some(user_id)) => return Ok(attachment_not_found()),
+        Ok(_) => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "You can only delete attachments of your own posts"
+            })));
+        }
+        Err(response) => return Ok(response),
+    }
+
+    match service.delete(attachment.id).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => {
+            log::error!("Failed to delete attachment: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete attachment"
+            })))
+        }
+    }
+}
+
+/// Whether an attachment's URLs must be signed: private files, and files of
+/// posts that are not public, which browsers can't fetch with a token.
+fn needs_signed_url(attachment: &Attachment, post: &PostWithUser) -> bool {
+    attachment.private || post.status != PostStatus::Published || post.hidden_at.is_some()
+}
+
+/// Fetches a post that `user_id` may attach files to.
+pub(super) async fn find_own_post(
+    pool: &PgPool,
+    post_id: Uuid,
+    user_id: Uuid,
+) -> Result<PostWithUser, HttpResponse> {
+    match find_post(pool, post_id).await {
+        Ok(post) if post.user_id == user_id => Ok(post),
+        Ok(post) if post.is_visible_to(Some(user_id)) => {
+            Err(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "You can only attach files to your own posts"
+            })))
+        }
+        Ok(_) => Err(post_not_found()),
+        Err(response) => Err(response),
+    }
+}
+
+async fn find_post(pool: &PgPool, post_id: Uuid) -> Result<PostWithUser, HttpResponse> {
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) => Ok(post),
+        Ok(None) => Err(post_not_found()),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+/// Reads the `file` and optional `private` fields of a multipart upload,
+/// rejecting files over `max_bytes` without buffering the rest.
+async fn read_upload_form(mut payload: Multipart, max_bytes: usize) -> Result<UploadedFile, HttpResponse> {
+    let mut file = None;
+    let mut private = false;
+
+    while let Some(field) = payload.next().await {
+        let field = field.map_err(invalid_form)?;
+
+        match field.name() {
+            Some("file") => {
+                let filename = field
+                    .content_disposition()
+                    .and_then(|disposition| disposition.get_filename())
+                    .map(clean_filename)
+                    .unwrap_or_default();
+                let declared_type = field.content_type().map(|mime| mime.essence_str().to_string());
+
+                let data = read_field(field, max_bytes).await?.ok_or_else(|| {
+                    HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                        "error": format!("File exceeds the {} byte upload limit", max_bytes)
+                    }))
+                })?;
+
+                let filename = if filename.is_empty() { "file".to_string() } else { filename };
+                file = Some((filename, declared_type, data));
+            }
+            Some("private") => {
+                let value = read_field(field, 16).await?.unwrap_or_default();
+                private = match value.as_ref() {
+                    b"true" => true,
+                    b"false" => false,
+                    _ => {
+                        return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                            "error": "Field 'private' must be true or false"
+                        })));
+                    }
+                };
+            }
+            name => {
+                return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("Unexpected form field '{}'", name.unwrap_or_default())
+                })));
+            }
+        }
+    }
+
+    match file {
+        Some((filename, declared_type, data)) if !data.is_empty() => Ok(UploadedFile {
+            filename,
+            declared_type,
+            data,
+            private,
+        }),
+        _ => Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "A non-empty 'file' field is required"
+        }))),
+    }
+}
+
+/// Reads a form field, or `None` once it grows past `max_bytes`.
+async fn read_field(mut field: Field, max_bytes: usize) -> Result<Option<Bytes>, HttpResponse> {
+    let mut data = BytesMut::new();
+
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk.map_err(invalid_form)?;
+        if data.len() + chunk.len() > max_bytes {
+            return Ok(None);
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(data.freeze()))
+}
+
+/// The last path component of a client-supplied filename, without control
+/// characters and cut to a sensible length.
+pub(super) fn clean_filename(filename: &str) -> String {
+    filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn invalid_form(e: actix_multipart::MultipartError) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid multipart form",
+        "details": e.to_string()
+    }))
+}
+
+fn post_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Post not found"
+    }))
+}
+
+fn attachment_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Attachment not found"
+    }))
+}
+
+fn storage_error() -> HttpResponse {
+    HttpResponse::InternalServerError().json(serde_json::json!({
+        "error": "Failed to read attachment"
+    }))
+}
\ No newline at end of file
@@ -149,6 +149,12 @@ pub async fn login(
         }
     }
 
+    if user.suspended_at.is_some() {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Account suspended"
+        })));
+    }
+
     // Generate JWT token
     let token = match auth_service.generate_token(user.id) {
         Ok(token) => token,
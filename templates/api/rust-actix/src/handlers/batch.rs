@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use actix_web::{
+    body::to_bytes,
+    dev::HttpServiceFactory,
+    http::{
+        header::{EntityTag, IfMatch},
+        StatusCode,
+    },
+    web, HttpMessage, HttpRequest, HttpResponse, Result,
+};
+use sqlx::{Acquire, PgConnection};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{BatchMode, BatchOperation, BatchPostRequest, BatchResult, Post, PostResponse},
+    services::PostService,
+};
+
+use super::posts::{apply_update, insert_post, trash_post};
+
+/// `POST /posts/batch`, registered by the posts scope.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::resource("/batch")
+        .wrap(AuthMiddleware)
+        .route(web::post().to(batch_posts))
+}
+
+/// What an accepted operation did.
+enum Applied {
+    Created(Post),
+    Updated(Post),
+    Deleted,
+}
+
+/// Runs up to `BATCH_MAX_OPERATIONS` creates, updates and deletes in one
+/// transaction, in order, with the same validation and ownership rules as
+/// the single-post endpoints.
+pub async fn batch_posts(
+    req: HttpRequest,
+    body: web::Json<BatchPostRequest>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let BatchPostRequest { mode, operations } = body.into_inner();
+
+    if operations.is_empty() || operations.len() > config.batch_max_operations {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("A batch must have between 1 and {} operations", config.batch_max_operations)
+        })));
+    }
+
+    // Validation errors keyed by operation index
+    let mut invalid: HashMap<usize, _> = operations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, operation)| operation.validate().err().map(|errors| (index, errors)))
+        .collect();
+
+    if mode == BatchMode::Atomic && !invalid.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": invalid
+        })));
+    }
+
+    let pool = db.get_pool();
+
+    // Each operation runs in a savepoint, so a refused one leaves no trace
+    // even when the rest of the batch is committed
+    let outcome = async {
+        let mut tx = pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(operations.len());
+
+        for (index, operation) in operations.iter().enumerate() {
+            if let Some(errors) = invalid.remove(&index) {
+                outcomes.push(Err(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Validation failed",
+                    "details": errors
+                }))));
+                continue;
+            }
+
+            let mut savepoint = tx.begin().await?;
+            match run_operation(&mut savepoint, &config, user_id, operation).await? {
+                Ok(applied) => {
+                    savepoint.commit().await?;
+                    outcomes.push(Ok(applied));
+                }
+                Err(response) => {
+                    savepoint.rollback().await?;
+                    if mode == BatchMode::Atomic {
+                        return Ok(Err((index, response)));
+                    }
+                    outcomes.push(Err(response));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(outcomes))
+    }
+    .await;
+
+    let outcomes = match outcome {
+        Ok(Ok(outcomes)) => outcomes,
+        Ok(Err((index, response))) => {
+            let status = response.status();
+            return Ok(HttpResponse::build(status).json(serde_json::json!({
+                "error": format!("Operation {} was refused; no changes were applied", index),
+                "failed": refused(index, response).await
+            })));
+        }
+        Err(e) => {
+            log::error!("Failed to run post batch: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to run batch"
+            })));
+        }
+    };
+
+    // Fetch the written posts with user information
+    let written: Vec<Uuid> = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            Ok(Applied::Created(post) | Applied::Updated(post)) => Some(post.id),
+            _ => None,
+        })
+        .collect();
+    let posts_with_user: HashMap<Uuid, _> = match PostService::new(pool).find_many_with_user(&written).await {
+        Ok(posts) => posts.into_iter().map(|post| (post.id, post)).collect(),
+        Err(e) => {
+            log::error!("Database error fetching batch posts: {:?}", e);
+            HashMap::new()
+        }
+    };
+
+    let mut results = Vec::with_capacity(outcomes.len());
+    for (index, outcome) in outcomes.into_iter().enumerate() {
+        let (status, post) = match outcome {
+            Ok(Applied::Created(post)) => (StatusCode::CREATED, post),
+            Ok(Applied::Updated(post)) => (StatusCode::OK, post),
+            Ok(Applied::Deleted) => {
+                results.push(BatchResult {
+                    index,
+                    status: StatusCode::NO_CONTENT.as_u16(),
+                    post: None,
+                    error: None,
+                });
+                continue;
+            }
+            Err(response) => {
+                results.push(refused(index, response).await);
+                continue;
+            }
+        };
+
+        // Posts trashed later in the batch are gone; show what this operation left
+        let post = match posts_with_user.get(&post.id) {
+            Some(post_with_user) => PostResponse::from(post_with_user.clone()),
+            None => PostResponse::from(post),
+        };
+        results.push(BatchResult {
+            index,
+            status: status.as_u16(),
+            post: Some(post),
+            error: None,
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "mode": mode,
+        "results": results
+    })))
+}
+
+async fn run_operation(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: Uuid,
+    operation: &BatchOperation,
+) -> Result<Result<Applied, HttpResponse>, sqlx::Error> {
+    Ok(match operation {
+        BatchOperation::Create { data } => insert_post(conn, config, user_id, data).await?.map(Applied::Created),
+        BatchOperation::Update { id, data, if_match } => {
+            apply_update(conn, config, user_id, *id, data, if_match.as_deref().map(parse_if_match))
+                .await?
+                .map(Applied::Updated)
+        }
+        BatchOperation::Delete { id, if_match } => {
+            trash_post(conn, config, user_id, *id, if_match.as_deref().map(parse_if_match))
+                .await?
+                .map(|()| Applied::Deleted)
+        }
+    })
+}
+
+/// Parses an `if_match` field like the `If-Match` header: `*` or a
+/// comma-separated list of entity tags. Tags that don't parse never match.
+fn parse_if_match(value: &str) -> IfMatch {
+    if value.trim() == "*" {
+        return IfMatch::Any;
+    }
+    IfMatch::Items(value.split(',').filter_map(|tag| tag.trim().parse::<EntityTag>().ok()).collect())
+}
+
+/// Turns the response a single-post endpoint would have sent into a batch
+/// result with the same status and error body.
+async fn refused(index: usize, response: HttpResponse) -> BatchResult {
+    let status = response.status().as_u16();
+    let error = to_bytes(response.into_body())
+        .await
+        .ok()
+        .and_then(|body| serde_json::from_slice(&body).ok());
+
+    BatchResult {
+        index,
+        status,
+        post: None,
+        error,
+    }
+}
\ No newline at end of file
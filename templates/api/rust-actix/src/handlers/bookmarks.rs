@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{
+        next_link, resolve_limit, BookmarkFolderRequest, BookmarkListQuery, BookmarkRequest,
+        BookmarkedPost, Cursor, Page, PostResponse,
+    },
+    services::{BookmarkService, PostService, ReactionService},
+};
+
+/// `PUT` and `DELETE /posts/{id}/bookmark`, registered by the posts scope.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::resource("/{id}/bookmark")
+        .wrap(AuthMiddleware)
+        .route(web::put().to(bookmark_post))
+        .route(web::delete().to(unbookmark_post))
+}
+
+/// Bookmark folder routes, registered by the (authenticated) users scope.
+pub fn folder_scope() -> impl HttpServiceFactory {
+    web::scope("/me/bookmark-folders")
+        .route("", web::get().to(get_folders))
+        .route("", web::post().to(create_folder))
+        .route("/{folder_id}", web::put().to(rename_folder))
+        .route("/{folder_id}", web::delete().to(delete_folder))
+}
+
+/// Bookmarks a post the current user can see, in the folder named by the
+/// optional body. Bookmarking it again moves it to that folder.
+pub async fn bookmark_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: Option<web::Json<BookmarkRequest>>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let post_id = path.into_inner();
+    let folder_id = body.map(|body| body.into_inner()).unwrap_or_default().folder_id;
+    let pool = db.get_pool();
+    let service = BookmarkService::new(pool);
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.is_visible_to(Some(user_id)) => {}
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    if let Some(folder_id) = folder_id {
+        match service.find_folder(user_id, folder_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Folder not found"
+                })));
+            }
+            Err(e) => {
+                log::error!("Database error: {:?}", e);
+                return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Database error"
+                })));
+            }
+        }
+    }
+
+    match service.save(user_id, post_id, folder_id).await {
+        Ok((bookmark, true)) => Ok(HttpResponse::Created().json(bookmark)),
+        Ok((bookmark, false)) => Ok(HttpResponse::Ok().json(bookmark)),
+        Err(e) => {
+            log::error!("Failed to bookmark post: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to bookmark post"
+            })))
+        }
+    }
+}
+
+pub async fn unbookmark_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    match BookmarkService::new(db.get_pool()).remove(user_id, path.into_inner()).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Bookmark not found"
+        }))),
+        Err(e) => {
+            log::error!("Failed to remove bookmark: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to remove bookmark"
+            })))
+        }
+    }
+}
+
+/// The current user's bookmarks, most recently saved first. Bookmarks of
+/// posts they can no longer see are left out.
+pub async fn get_bookmarks(
+    req: HttpRequest,
+    query: web::Query<BookmarkListQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "bookmarked_at" && cursor.timestamp().is_some() => Some(cursor),
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            })));
+        }
+        None => None,
+    };
+
+    let pool = db.get_pool();
+
+    // Fetch one extra row to find out whether another page follows
+    let bookmarks = async {
+        let mut bookmarks = BookmarkService::new(pool)
+            .list(user_id, query.folder_id, cursor.as_ref(), limit + 1)
+            .await?;
+        let has_more = bookmarks.len() as i64 > limit;
+        bookmarks.truncate(limit as usize);
+
+        let post_ids: Vec<Uuid> = bookmarks.iter().map(|bookmark| bookmark.post_id).collect();
+        let posts = PostService::new(pool).find_many_with_user(&post_ids).await?;
+        let reactions = ReactionService::new(pool).for_viewer(Some(user_id), &post_ids).await?;
+
+        Ok::<_, sqlx::Error>((bookmarks, has_more, posts, reactions))
+    }
+    .await;
+
+    match bookmarks {
+        Ok((bookmarks, has_more, posts, reactions)) => {
+            let next_cursor = if has_more {
+                bookmarks
+                    .last()
+                    .map(|bookmark| Cursor::for_timestamp("bookmarked_at", bookmark.created_at, bookmark.post_id).encode())
+            } else {
+                None
+            };
+
+            let mut posts: HashMap<Uuid, _> = posts.into_iter().map(|post| (post.id, post)).collect();
+            let data = bookmarks
+                .into_iter()
+                .filter_map(|bookmark| {
+                    let mut post = PostResponse::from(posts.remove(&bookmark.post_id)?)
+                        .with_viewer_reactions(reactions.as_ref());
+                    post.bookmarked = Some(true);
+                    Some(BookmarkedPost {
+                        folder_id: bookmark.folder_id,
+                        bookmarked_at: bookmark.created_at,
+                        post,
+                    })
+                })
+                .collect();
+
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(Page { data, next_cursor }))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch bookmarks"
+            })))
+        }
+    }
+}
+
+pub async fn get_folders(req: HttpRequest, db: web::Data<Database>) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    match BookmarkService::new(db.get_pool()).folders(user_id).await {
+        Ok(folders) => Ok(HttpResponse::Ok().json(folders)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch bookmark folders"
+            })))
+        }
+    }
+}
+
+pub async fn create_folder(
+    req: HttpRequest,
+    body: web::Json<BookmarkFolderRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let service = BookmarkService::new(db.get_pool());
+
+    let folder = async {
+        if service.folder_name_taken(user_id, &body.name, None).await? {
+            return Ok(None);
+        }
+        service.create_folder(user_id, &body.name).await.map(Some)
+    }
+    .await;
+
+    match folder {
+        Ok(Some(folder)) => Ok(HttpResponse::Created().json(folder)),
+        Ok(None) => Ok(folder_name_conflict()),
+        Err(e) => {
+            log::error!("Failed to create bookmark folder: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create bookmark folder"
+            })))
+        }
+    }
+}
+
+pub async fn rename_folder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<BookmarkFolderRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let folder_id = path.into_inner();
+    let service = BookmarkService::new(db.get_pool());
+
+    let folder = async {
+        if service.folder_name_taken(user_id, &body.name, Some(folder_id)).await? {
+            return Ok(Err(folder_name_conflict()));
+        }
+        let folder = service.rename_folder(user_id, folder_id, &body.name).await?;
+        Ok::<_, sqlx::Error>(Ok(folder))
+    }
+    .await;
+
+    match folder {
+        Ok(Ok(Some(folder))) => Ok(HttpResponse::Ok().json(folder)),
+        Ok(Ok(None)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Folder not found"
+        }))),
+        Ok(Err(response)) => Ok(response),
+        Err(e) => {
+            log::error!("Failed to rename bookmark folder: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to rename bookmark folder"
+            })))
+        }
+    }
+}
+
+/// Deletes a folder; its bookmarks are kept, unfiled.
+pub async fn delete_folder(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    match BookmarkService::new(db.get_pool()).delete_folder(user_id, path.into_inner()).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Folder not found"
+        }))),
+        Err(e) => {
+            log::error!("Failed to delete bookmark folder: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete bookmark folder"
+            })))
+        }
+    }
+}
+
+fn folder_name_conflict() -> HttpResponse {
+    HttpResponse::Conflict().json(serde_json::json!({
+        "error": "A bookmark folder with this name already exists"
+    }))
+}
\ No newline at end of file
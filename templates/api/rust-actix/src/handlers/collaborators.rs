@@ -0,0 +1,274 @@
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{
+        next_link, resolve_limit, AddCollaboratorRequest, Cursor, FieldsQuery, Page, PostResponse,
+        PostWithUser, SharedPostsQuery,
+    },
+    services::{CollaboratorService, PostColumns, PostService},
+};
+
+use super::fields::field_selection;
+
+/// Collaborator routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::scope("/{id}/collaborators")
+        .wrap(AuthMiddleware)
+        .route("", web::get().to(get_collaborators))
+        .route("", web::post().to(add_collaborator))
+        .route("/{user_id}", web::delete().to(remove_collaborator))
+}
+
+/// `GET /posts/shared`, registered by the posts scope.
+pub fn shared_posts() -> impl HttpServiceFactory {
+    web::resource("/shared")
+        .wrap(AuthMiddleware)
+        .route(web::get().to(get_shared_posts))
+}
+
+pub async fn get_collaborators(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    match find_post(db.get_pool(), path.into_inner()).await {
+        Ok(post) if post.is_visible_to(Some(user_id)) => Ok(HttpResponse::Ok().json(post.collaborators.0)),
+        Ok(_) => Ok(post_not_found()),
+        Err(response) => Ok(response),
+    }
+}
+
+/// Shares a post with another user by email, or changes their role if it
+/// is already shared with them.
+pub async fn add_collaborator(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<AddCollaboratorRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let pool = db.get_pool();
+
+    let post = match find_post(pool, path.into_inner()).await {
+        Ok(post) if post.user_id == user_id => post,
+        Ok(post) if post.is_visible_to(Some(user_id)) => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Only the post's author can share it"
+            })));
+        }
+        Ok(_) => return Ok(post_not_found()),
+        Err(response) => return Ok(response),
+    };
+
+    let invitee = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", body.email)
+        .fetch_optional(pool)
+        .await;
+
+    let invitee = match invitee {
+        Ok(Some(id)) if id == post.user_id => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "The author already has full access to the post"
+            })));
+        }
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "User not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    match CollaboratorService::new(pool)
+        .upsert(post.id, invitee, body.role, user_id)
+        .await
+    {
+        Ok((collaborator, true)) => Ok(HttpResponse::Created().json(collaborator)),
+        Ok((collaborator, false)) => Ok(HttpResponse::Ok().json(collaborator)),
+        Err(e) => {
+            log::error!("Failed to share post: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to share post"
+            })))
+        }
+    }
+}
+
+/// Revokes a collaborator's access. Authors can remove anyone;
+/// collaborators can remove themselves.
+pub async fn remove_collaborator(
+    req: HttpRequest,
+    path: web::Path<(Uuid, Uuid)>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let (post_id, collaborator_id) = path.into_inner();
+    let pool = db.get_pool();
+
+    match find_post(pool, post_id).await {
+        Ok(post) if post.user_id == user_id || collaborator_id == user_id => {}
+        Ok(post) if post.is_visible_to(Some(user_id)) => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Only the post's author can remove other collaborators"
+            })));
+        }
+        Ok(_) => return Ok(post_not_found()),
+        Err(response) => return Ok(response),
+    }
+
+    match CollaboratorService::new(pool).remove(post_id, collaborator_id).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Collaborator not found"
+        }))),
+        Err(e) => {
+            log::error!("Failed to remove collaborator: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to remove collaborator"
+            })))
+        }
+    }
+}
+
+/// Posts other authors shared with the current user, most recently
+/// updated first.
+pub async fn get_shared_posts(
+    req: HttpRequest,
+    query: web::Query<SharedPostsQuery>,
+    fields: web::Query<FieldsQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "updated_at" && cursor.timestamp().is_some() => Some(cursor),
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            })));
+        }
+        None => None,
+    };
+
+    let posts = PostService::new(db.get_pool())
+        .list_shared(user_id, query.role, cursor.as_ref(), limit + 1, PostColumns::for_selection(&selection))
+        .await;
+
+    match posts {
+        Ok(mut posts) => {
+            let has_more = posts.len() as i64 > limit;
+            posts.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                posts
+                    .last()
+                    .map(|post| Cursor::for_timestamp("updated_at", post.updated_at, post.id).encode())
+            } else {
+                None
+            };
+
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(Page {
+                data: posts
+                    .into_iter()
+                    .map(|post| selection.project(&PostResponse::from(post)))
+                    .collect(),
+                next_cursor,
+            }))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch shared posts"
+            })))
+        }
+    }
+}
+
+async fn find_post(pool: &PgPool, post_id: Uuid) -> Result<PostWithUser, HttpResponse> {
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) => Ok(post),
+        Ok(None) => Err(post_not_found()),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+fn post_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Post not found"
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,426 @@
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::{viewer_id, AuthMiddleware},
+    models::{
+        next_link, resolve_limit, Comment, CommentListQuery, CommentResponse,
+        CreateCommentRequest, Cursor, Page, UpdateCommentRequest,
+    },
+    services::{build_comment_tree, CommentService, PostService},
+};
+
+/// Comment routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::scope("/{id}/comments")
+        .route("", web::get().to(get_comments))
+        .service(
+            web::scope("")
+                .wrap(AuthMiddleware)
+                .route("", web::post().to(create_comment))
+        )
+}
+
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/comments")
+        .wrap(AuthMiddleware)
+        .route("/{id}", web::put().to(update_comment))
+        .route("/{id}", web::delete().to(delete_comment))
+}
+
+pub async fn get_comments(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<CommentListQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let max_depth = match query.depth {
+        None => config.comment_max_depth,
+        Some(depth) if depth < 0 => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "depth must not be negative"
+            })));
+        }
+        Some(depth) => depth.min(config.comment_max_depth),
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "created_at" && cursor.timestamp().is_some() => Some(cursor),
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            })));
+        }
+        None => None,
+    };
+
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+    let viewer = viewer_id(&req).await;
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.is_visible_to(viewer) => {}
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    let service = CommentService::new(pool);
+
+    // Fetch one extra thread to find out whether another page follows
+    let comments = async {
+        let mut roots = service.list_roots(post_id, cursor.as_ref(), limit + 1).await?;
+        let has_more = roots.len() as i64 > limit;
+        roots.truncate(limit as usize);
+
+        let next_cursor = if has_more {
+            roots
+                .last()
+                .map(|root| Cursor::for_timestamp("created_at", root.created_at, root.id).encode())
+        } else {
+            None
+        };
+
+        let root_ids: Vec<Uuid> = roots.iter().map(|root| root.id).collect();
+        let replies = service.list_replies(&root_ids, max_depth).await?;
+
+        Ok::<_, sqlx::Error>(Page {
+            data: build_comment_tree(roots, replies, max_depth),
+            next_cursor,
+        })
+    }
+    .await;
+
+    match comments {
+        Ok(page) => {
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &page.next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+            Ok(response.json(page))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch comments"
+            })))
+        }
+    }
+}
+
+pub async fn create_comment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<CreateCommentRequest>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.is_visible_to(Some(user_id)) => {}
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    let depth = match body.parent_id {
+        None => 0,
+        Some(parent_id) => {
+            let parent = sqlx::query_scalar!(
+                r#"
+                SELECT depth FROM comments
+                WHERE id = $1 AND post_id = $2 AND deleted_at IS NULL
+                "#,
+                parent_id,
+                post_id
+            )
+            .fetch_optional(pool)
+            .await;
+
+            match parent {
+                Ok(Some(depth)) if depth < config.comment_max_depth => depth + 1,
+                Ok(Some(_)) => {
+                    return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": format!("Replies cannot be nested more than {} levels deep", config.comment_max_depth)
+                    })));
+                }
+                Ok(None) => {
+                    return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": "Parent comment not found on this post"
+                    })));
+                }
+                Err(e) => {
+                    log::error!("Database error: {:?}", e);
+                    return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Database error"
+                    })));
+                }
+            }
+        }
+    };
+
+    // Create comment and bump the post's counter together
+    let comment = async {
+        let mut tx = pool.begin().await?;
+
+        let comment = sqlx::query_as!(
+            Comment,
+            r#"
+            INSERT INTO comments (post_id, user_id, parent_id, depth, content)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, post_id, user_id, parent_id, depth, content, created_at, updated_at, deleted_at
+            "#,
+            post_id,
+            user_id,
+            body.parent_id,
+            depth,
+            body.content
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE posts SET comment_count = comment_count + 1 WHERE id = $1",
+            post_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(comment)
+    }
+    .await;
+
+    match comment {
+        Ok(comment) => Ok(HttpResponse::Created().json(CommentResponse::from(comment))),
+        Err(e) => {
+            log::error!("Failed to create comment: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create comment"
+            })))
+        }
+    }
+}
+
+pub async fn update_comment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<UpdateCommentRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let comment_id = path.into_inner();
+    let pool = db.get_pool();
+
+    let existing_comment = match find_live_comment(pool, comment_id).await {
+        Ok(comment) => comment,
+        Err(response) => return Ok(response),
+    };
+
+    if existing_comment.user_id != user_id {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "You can only edit your own comments"
+        })));
+    }
+
+    let comment = sqlx::query_as!(
+        Comment,
+        r#"
+        UPDATE comments
+        SET content = $1, updated_at = NOW()
+        WHERE id = $2
+        RETURNING id, post_id, user_id, parent_id, depth, content, created_at, updated_at, deleted_at
+        "#,
+        body.content,
+        comment_id
+    )
+    .fetch_one(pool)
+    .await;
+
+    match comment {
+        Ok(comment) => Ok(HttpResponse::Ok().json(CommentResponse::from(comment))),
+        Err(e) => {
+            log::error!("Failed to update comment: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update comment"
+            })))
+        }
+    }
+}
+
+pub async fn delete_comment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let comment_id = path.into_inner();
+    let pool = db.get_pool();
+
+    let existing_comment = match find_live_comment(pool, comment_id).await {
+        Ok(comment) => comment,
+        Err(response) => return Ok(response),
+    };
+
+    // Post owners moderate the discussion on their posts
+    let post_owner = sqlx::query_scalar!(
+        "SELECT user_id FROM posts WHERE id = $1",
+        existing_comment.post_id
+    )
+    .fetch_one(pool)
+    .await;
+
+    match post_owner {
+        Ok(owner_id) if owner_id == user_id || existing_comment.user_id == user_id => {}
+        Ok(_) => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "You can only delete your own comments or comments on your posts"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    // Keep the row so replies stay attached; the thread shows a placeholder
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        let deleted = sqlx::query!(
+            "UPDATE comments SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+            comment_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if deleted > 0 {
+            sqlx::query!(
+                "UPDATE posts SET comment_count = comment_count - 1 WHERE id = $1",
+                existing_comment.post_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => {
+            log::error!("Failed to delete comment: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete comment"
+            })))
+        }
+    }
+}
+
+/// Loads a comment that is neither deleted nor on a trashed post.
+async fn find_live_comment(pool: &PgPool, comment_id: Uuid) -> Result<Comment, HttpResponse> {
+    let comment = sqlx::query_as!(
+        Comment,
+        r#"
+        SELECT c.id, c.post_id, c.user_id, c.parent_id, c.depth, c.content,
+               c.created_at, c.updated_at, c.deleted_at
+        FROM comments c
+        JOIN posts p ON c.post_id = p.id
+        WHERE c.id = $1 AND c.deleted_at IS NULL AND p.deleted_at IS NULL
+        "#,
+        comment_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match comment {
+        Ok(Some(comment)) => Ok(comment),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Comment not found"
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+// `check_if_match` fails with the finished 412/428 response, which handlers return
+// unchanged; it is only built on the error path, so it is not boxed.
+#![allow(clippy::result_large_err)]
+
+use actix_web::{
+    http::header::{
+        EntityTag, ETag, HttpDate, IfMatch, IfModifiedSince, IfNoneMatch, LastModified,
+        CACHE_CONTROL, VARY,
+    },
+    HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+
+use crate::config::Config;
+
+/// Strong entity tag for a post version, derived from its `updated_at`,
+/// which every write to the post bumps. Returned by writes and compared by
+/// `If-Match`.
+pub fn post_etag(updated_at: DateTime<Utc>) -> EntityTag {
+    EntityTag::new_strong(updated_at.timestamp_micros().to_string())
+}
+
+/// Strong entity tag for a post as read: its version followed by a digest of
+/// the serialized body, so that it also changes with counters, viewer state
+/// and field selections that do not bump `updated_at`. `If-Match` only
+/// compares the version.
+pub fn post_read_etag<T: Serialize>(updated_at: DateTime<Utc>, body: &T) -> EntityTag {
+    EntityTag::new_strong(format!("{}-{}", updated_at.timestamp_micros(), body_digest(body)))
+}
+
+/// Weak entity tag for a page of results, hashed from its serialized form
+/// so that it also changes with counters that do not bump `updated_at`.
+pub fn list_etag<T: Serialize>(page: &T) -> EntityTag {
+    EntityTag::new_weak(body_digest(page))
+}
+
+fn body_digest<T: Serialize>(body: &T) -> String {
+    let body = serde_json::to_vec(body).unwrap_or_default();
+    let digest = Sha256::digest(&body);
+    digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Validators of a readable representation, used to answer conditional
+/// GETs with `304 Not Modified`.
+pub struct CacheValidators {
+    pub etag: EntityTag,
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Whether the representation was negotiated on `Accept-Language`.
+    pub language_negotiated: bool,
+}
+
+impl CacheValidators {
+    pub fn new(etag: EntityTag, last_modified: Option<DateTime<Utc>>) -> Self {
+        Self {
+            etag,
+            last_modified,
+            language_negotiated: false,
+        }
+    }
+
+    pub fn language_negotiated(mut self) -> Self {
+        self.language_negotiated = true;
+        self
+    }
+
+    /// Whether the client's cached copy is still current. `If-None-Match`
+    /// takes precedence over `If-Modified-Since` (RFC 9110, section 13.2.2).
+    pub fn is_fresh(&self, req: &HttpRequest) -> bool {
+        if let Some(if_none_match) = req.get_header::<IfNoneMatch>() {
+            return match if_none_match {
+                IfNoneMatch::Any => true,
+                IfNoneMatch::Items(tags) => tags.iter().any(|tag| tag.weak_eq(&self.etag)),
+            };
+        }
+
+        match (req.get_header::<IfModifiedSince>(), self.last_modified) {
+            // HTTP dates have whole-second precision
+            (Some(IfModifiedSince(since)), Some(last_modified)) => {
+                last_modified.timestamp() <= DateTime::<Utc>::from(SystemTime::from(since)).timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds the validators and caching policy to a response.
+    pub fn apply(&self, response: &mut HttpResponseBuilder, cache_control: &str) {
+        response
+            .insert_header(ETag(self.etag.clone()))
+            .insert_header((CACHE_CONTROL, cache_control))
+            // Drafts are only visible to their author, so the representation
+            // depends on who is asking
+            .insert_header((
+                VARY,
+                if self.language_negotiated {
+                    "Authorization, Accept-Language"
+                } else {
+                    "Authorization"
+                },
+            ));
+
+        if let Some(last_modified) = self.last_modified {
+            response.insert_header(LastModified(HttpDate::from(SystemTime::from(last_modified))));
+        }
+    }
+
+    pub fn not_modified(&self, cache_control: &str) -> HttpResponse {
+        let mut response = HttpResponse::NotModified();
+        self.apply(&mut response, cache_control);
+        response.finish()
+    }
+}
+
+fn parse_post_etag(tag: &EntityTag) -> Option<DateTime<Utc>> {
+    // If-Match uses the strong comparison function, so weak tags never match
+    if tag.weak {
+        return None;
+    }
+    // Read tags append a body digest to the version
+    let version = tag.tag().split('-').next().unwrap_or_default();
+    version.parse().ok().and_then(DateTime::from_timestamp_micros)
+}
+
+/// Evaluates an `If-Match` precondition for a write to a post currently at
+/// `current`.
+///
+/// Returns the `updated_at` values the write may still apply to, or `None`
+/// when the write is unconditional (no header, or `*`). Callers must put the
+/// returned values in the `WHERE` clause of the write itself so that the
+/// check and the write are atomic.
+pub fn check_if_match(
+    if_match: Option<IfMatch>,
+    config: &Config,
+    current: DateTime<Utc>,
+) -> Result<Option<Vec<DateTime<Utc>>>, HttpResponse> {
+    let if_match = match if_match {
+        Some(IfMatch::Any) => return Ok(None),
+        Some(IfMatch::Items(tags)) => tags,
+        None if config.require_if_match => {
+            return Err(HttpResponse::PreconditionRequired().json(serde_json::json!({
+                "error": "If-Match header required"
+            })));
+        }
+        None => return Ok(None),
+    };
+
+    let versions: Vec<DateTime<Utc>> = if_match.iter().filter_map(parse_post_etag).collect();
+
+    if !versions.contains(&current) {
+        return Err(precondition_failed());
+    }
+
+    Ok(Some(versions))
+}
+
+pub fn precondition_failed() -> HttpResponse {
+    HttpResponse::PreconditionFailed().json(serde_json::json!({
+        "error": "Post has been modified; fetch the latest version and retry"
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn version() -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap()
+    }
+
+    #[test]
+    fn read_etag_changes_with_comment_count() {
+        let tag = post_read_etag(version(), &json!({"id": 1, "comment_count": 1}));
+
+        assert!(tag.strong_eq(&post_read_etag(version(), &json!({"id": 1, "comment_count": 1}))));
+        assert!(!tag.weak_eq(&post_read_etag(version(), &json!({"id": 1, "comment_count": 2}))));
+    }
+
+    #[test]
+    fn read_etag_changes_with_reactions() {
+        let tag = post_read_etag(version(), &json!({"reaction_counts": {"like": 1}, "viewer_reactions": ["like"]}));
+
+        assert!(!tag.weak_eq(&post_read_etag(
+            version(),
+            &json!({"reaction_counts": {"like": 2}, "viewer_reactions": ["like"]})
+        )));
+        assert!(!tag.weak_eq(&post_read_etag(
+            version(),
+            &json!({"reaction_counts": {"like": 1}, "viewer_reactions": []})
+        )));
+    }
+
+    #[test]
+    fn read_etag_changes_with_viewer_bookmark() {
+        let tag = post_read_etag(version(), &json!({"id": 1, "bookmarked": false}));
+
+        assert!(!tag.weak_eq(&post_read_etag(version(), &json!({"id": 1, "bookmarked": true}))));
+    }
+
+    #[test]
+    fn read_etag_differs_between_field_selections() {
+        let full = post_read_etag(version(), &json!({"id": 1, "title": "T", "content": "C"}));
+
+        assert!(!full.weak_eq(&post_read_etag(version(), &json!({"id": 1, "title": "T"}))));
+    }
+
+    #[test]
+    fn if_match_reads_the_version_of_read_and_write_tags() {
+        assert_eq!(parse_post_etag(&post_etag(version())), Some(version()));
+        assert_eq!(parse_post_etag(&post_read_etag(version(), &json!({}))), Some(version()));
+        assert_eq!(parse_post_etag(&list_etag(&json!({}))), None);
+        assert_eq!(parse_post_etag(&EntityTag::new_strong("abc".to_string())), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+use actix_web::{http::header::CONTENT_TYPE, web, HttpRequest, HttpResponse, Result, Scope};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    models::{FeedFormat, FeedQuery, PostListQuery, PostStatus},
+    services::{feed_updated, render_feed, FeedInfo, PostColumns, PostService},
+};
+
+use super::conditional::{list_etag, CacheValidators};
+
+pub fn config() -> Scope {
+    web::scope("/feeds")
+        .route("/posts.{format}", web::get().to(get_posts_feed))
+        .route("/users/{id}/posts.{format}", web::get().to(get_author_feed))
+}
+
+/// Latest published posts as RSS 2.0, Atom or JSON Feed 1.1.
+pub async fn get_posts_feed(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FeedQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let Some(format) = FeedFormat::from_extension(&path) else {
+        return Ok(feed_not_found());
+    };
+
+    let base_url = base_url(&req, &config);
+    let info = FeedInfo {
+        title: config.feed_title.clone(),
+        description: format!("Latest posts from {}", config.feed_title),
+        home_page_url: format!("{}/api/v1/posts", base_url),
+        feed_url: feed_url(&req, &base_url),
+        post_url_base: format!("{}/api/v1/posts/by-slug", base_url),
+    };
+
+    render(&req, db.get_pool(), &config, format, info, None, &query).await
+}
+
+/// Latest published posts of one author.
+pub async fn get_author_feed(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    query: web::Query<FeedQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let (author_id, extension) = path.into_inner();
+    let Some(format) = FeedFormat::from_extension(&extension) else {
+        return Ok(feed_not_found());
+    };
+
+    let pool = db.get_pool();
+
+    let author = sqlx::query!(
+        "SELECT first_name, last_name FROM users WHERE id = $1",
+        author_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    let author = match author {
+        Ok(Some(author)) => format!("{} {}", author.first_name, author.last_name),
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "User not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    let base_url = base_url(&req, &config);
+    let info = FeedInfo {
+        title: format!("{} by {}", config.feed_title, author),
+        description: format!("Latest posts by {}", author),
+        home_page_url: format!("{}/api/v1/posts?author_id={}", base_url, author_id),
+        feed_url: feed_url(&req, &base_url),
+        post_url_base: format!("{}/api/v1/posts/by-slug", base_url),
+    };
+
+    render(&req, pool, &config, format, info, Some(author_id), &query).await
+}
+
+/// Fetches the posts through the same listing as `GET /posts`, as an
+/// anonymous viewer, and answers conditional GETs against the rendered feed.
+async fn render(
+    req: &HttpRequest,
+    pool: &PgPool,
+    config: &Config,
+    format: FeedFormat,
+    info: FeedInfo,
+    author_id: Option<Uuid>,
+    query: &FeedQuery,
+) -> Result<HttpResponse> {
+    if let Err(errors) = query.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let list_query = PostListQuery {
+        author_id,
+        status: Some(PostStatus::Published),
+        tag: query.tag.clone(),
+        ..Default::default()
+    };
+
+    let posts = PostService::new(pool)
+        .list(&list_query, None, None, config.feed_size, PostColumns::ALL)
+        .await;
+
+    match posts {
+        Ok(posts) => {
+            let body = render_feed(format, &info, &posts);
+            let validators = CacheValidators::new(list_etag(&body), Some(feed_updated(&posts)));
+            if validators.is_fresh(req) {
+                return Ok(validators.not_modified(&config.cache_control_post_list));
+            }
+
+            let mut response = HttpResponse::Ok();
+            validators.apply(&mut response, &config.cache_control_post_list);
+            Ok(response.insert_header((CONTENT_TYPE, format.content_type())).body(body))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch posts"
+            })))
+        }
+    }
+}
+
+/// Origin for the absolute links in a feed. Feeds are cacheable, so the
+/// client's `Host` and forwarded headers only count behind a trusted proxy.
+fn base_url(req: &HttpRequest, config: &Config) -> String {
+    if let Some(url) = &config.public_base_url {
+        return url.clone();
+    }
+    if config.trust_proxy_headers {
+        let connection = req.connection_info();
+        return format!("{}://{}", connection.scheme(), connection.host());
+    }
+
+    let app_config = req.app_config();
+    let scheme = if app_config.secure() { "https" } else { "http" };
+    format!("{}://{}", scheme, app_config.host())
+}
+
+fn feed_url(req: &HttpRequest, base_url: &str) -> String {
+    match req.query_string() {
+        "" => format!("{}{}", base_url, req.path()),
+        query => format!("{}{}?{}", base_url, req.path(), query),
+    }
+}
+
+fn feed_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Unknown feed format; use .rss, .atom or .json"
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+// A rejected `?fields=`/`?expand=` is answered with the 400 response built here,
+// which callers return as it is rather than unboxing it first.
+#![allow(clippy::result_large_err)]
+
+use actix_web::HttpResponse;
+
+use crate::models::{FieldSelection, FieldsQuery, SparseFields};
+
+/// Parses `?fields=` and `?expand=` for a `T` response, answering unknown
+/// names with `400`.
+pub fn field_selection<T: SparseFields>(query: &FieldsQuery) -> Result<FieldSelection, HttpResponse> {
+    FieldSelection::parse::<T>(query).map_err(|message| {
+        HttpResponse::BadRequest().json(serde_json::json!({
+            "error": message,
+            "fields": T::FIELDS,
+            "expand": T::EXPANSIONS
+        }))
+    })
+}
\ No newline at end of file
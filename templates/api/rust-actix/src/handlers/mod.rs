@@ -1,3 +1,21 @@
+pub mod attachments;
 pub mod auth;
+pub mod batch;
+pub mod bookmarks;
+pub mod collaborators;
+pub mod comments;
+pub mod conditional;
+pub mod feeds;
+pub mod fields;
 pub mod health;
-pub mod posts;
\ No newline at end of file
+pub mod moderation;
+pub mod patch;
+pub mod posts;
+pub mod reactions;
+pub mod revisions;
+pub mod stats;
+pub mod tags;
+pub mod transfer;
+pub mod translations;
+pub mod uploads;
+pub mod users;
\ No newline at end of file
@@ -0,0 +1,347 @@
+// Helpers below fail with the response the handler returns (403 for non-moderators,
+// 400 for bad cursors); that only happens on the error path.
+#![allow(clippy::result_large_err)]
+
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{
+        next_link, resolve_limit, CreateReportRequest, Cursor, ModerationActionKind,
+        ModerationActionRequest, ModerationLogQuery, Page, ReportQueueQuery, ReportStatus,
+    },
+    services::{ModerationService, PostService, ReportOutcome},
+};
+
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/moderation")
+        .wrap(AuthMiddleware)
+        .route("/reports", web::get().to(get_report_queue))
+        .route("/reports/{id}/actions", web::post().to(act_on_report))
+        .route("/actions", web::get().to(get_moderation_actions))
+}
+
+/// Report routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::resource("/{id}/reports")
+        .wrap(AuthMiddleware)
+        .route(web::post().to(report_post))
+}
+
+pub async fn report_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<CreateReportRequest>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.user_id == user_id => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "You cannot report your own post"
+            })));
+        }
+        Ok(Some(post)) if post.is_visible_to(Some(user_id)) => {}
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    let outcome = ModerationService::new(pool)
+        .report(
+            post_id,
+            user_id,
+            body.reason,
+            body.details.as_deref(),
+            config.report_hide_threshold,
+        )
+        .await;
+
+    match outcome {
+        Ok(ReportOutcome::Created { report, hidden }) => {
+            if hidden {
+                log::info!("Post {} hidden after reaching the report threshold", post_id);
+            }
+            Ok(HttpResponse::Created().json(report))
+        }
+        Ok(ReportOutcome::Duplicate) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "You already reported this post"
+        }))),
+        Err(e) => {
+            log::error!("Failed to create report: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create report"
+            })))
+        }
+    }
+}
+
+/// The moderation queue: reports with a given status (default `open`),
+/// oldest first.
+pub async fn get_report_queue(
+    req: HttpRequest,
+    query: web::Query<ReportQueueQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let pool = db.get_pool();
+    if let Err(response) = require_moderator(&req, pool).await {
+        return Ok(response);
+    }
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let cursor = match decode_cursor(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(response) => return Ok(response),
+    };
+
+    match ModerationService::new(pool).queue(&query, cursor.as_ref(), limit + 1).await {
+        Ok(mut reports) => {
+            let has_more = reports.len() as i64 > limit;
+            reports.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                reports.last().map(|queued| {
+                    Cursor::for_timestamp("created_at", queued.report.created_at, queued.report.id).encode()
+                })
+            } else {
+                None
+            };
+
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(Page {
+                data: reports,
+                next_cursor,
+            }))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch reports"
+            })))
+        }
+    }
+}
+
+/// Dismisses a report, hides the reported post or suspends its author.
+/// Every open report of the post is resolved along with it.
+pub async fn act_on_report(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<ModerationActionRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let pool = db.get_pool();
+    let moderator_id = match require_moderator(&req, pool).await {
+        Ok(id) => id,
+        Err(response) => return Ok(response),
+    };
+
+    let service = ModerationService::new(pool);
+
+    let report = match service.find_report(path.into_inner()).await {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Report not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    if report.status != ReportStatus::Open {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Report has already been resolved"
+        })));
+    }
+
+    if body.action == ModerationActionKind::SuspendAuthor {
+        let author_id = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", report.post_id)
+            .fetch_optional(pool)
+            .await;
+
+        match author_id {
+            Ok(Some(author_id)) if author_id == moderator_id => {
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "You cannot suspend yourself"
+                })));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("Database error: {:?}", e);
+                return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Database error"
+                })));
+            }
+        }
+    }
+
+    match service
+        .act(&report, moderator_id, body.action, body.note.as_deref())
+        .await
+    {
+        Ok(Some(action)) => Ok(HttpResponse::Created().json(action)),
+        Ok(None) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Report has already been resolved"
+        }))),
+        Err(e) => {
+            log::error!("Failed to apply moderation action: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to apply moderation action"
+            })))
+        }
+    }
+}
+
+/// The moderation audit log, newest first.
+pub async fn get_moderation_actions(
+    req: HttpRequest,
+    query: web::Query<ModerationLogQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let pool = db.get_pool();
+    if let Err(response) = require_moderator(&req, pool).await {
+        return Ok(response);
+    }
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let cursor = match decode_cursor(query.cursor.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(response) => return Ok(response),
+    };
+
+    let actions = ModerationService::new(pool)
+        .actions(query.post_id, query.moderator_id, cursor.as_ref(), limit + 1)
+        .await;
+
+    match actions {
+        Ok(mut actions) => {
+            let has_more = actions.len() as i64 > limit;
+            actions.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                actions
+                    .last()
+                    .map(|action| Cursor::for_timestamp("created_at", action.created_at, action.id).encode())
+            } else {
+                None
+            };
+
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(Page {
+                data: actions,
+                next_cursor,
+            }))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch moderation actions"
+            })))
+        }
+    }
+}
+
+/// Resolves the current user, who must be a moderator.
+async fn require_moderator(req: &HttpRequest, pool: &PgPool) -> Result<Uuid, HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Err(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    match ModerationService::new(pool).is_moderator(user_id).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Moderator access required"
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+fn decode_cursor(token: Option<&str>) -> Result<Option<Cursor>, HttpResponse> {
+    match token.map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "created_at" && cursor.timestamp().is_some() => Ok(Some(cursor)),
+        Some(_) => Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid cursor"
+        }))),
+        None => Ok(None),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,144 @@
+// `apply_patch` fails with the 400/409/415/422 response to send back, so its error
+// is as large as a response; it is only built when a patch is rejected.
+#![allow(clippy::result_large_err)]
+
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
+use json_patch::{Patch, PatchErrorKind};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// JSON Merge Patch (RFC 7396).
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+/// JSON Patch (RFC 6902).
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+/// Applies the body of a `PATCH` request to `current`, the editable fields
+/// of a resource, as the patch format named by its `Content-Type`.
+///
+/// Status codes follow RFC 5789: a malformed patch is `400`, one that
+/// cannot be applied to the document `422`, a failed JSON Patch `test`
+/// `409`, and a result that is not a valid document again `422`. Callers
+/// still have to validate the returned document.
+pub fn apply_patch<T: Serialize + DeserializeOwned>(
+    req: &HttpRequest,
+    body: &[u8],
+    current: &T,
+) -> Result<T, HttpResponse> {
+    let mut document = serde_json::to_value(current).map_err(|e| {
+        log::error!("Failed to serialize patch target: {:?}", e);
+        HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to apply patch"
+        }))
+    })?;
+
+    let content_type = req.content_type();
+    if content_type.eq_ignore_ascii_case(MERGE_PATCH) {
+        let patch: Value = serde_json::from_slice(body).map_err(|e| malformed_patch(&e))?;
+        json_patch::merge(&mut document, &patch);
+    } else if content_type.eq_ignore_ascii_case(JSON_PATCH) {
+        let patch: Patch = serde_json::from_slice(body).map_err(|e| malformed_patch(&e))?;
+        if let Err(e) = json_patch::patch(&mut document, &patch) {
+            let response = match e.kind {
+                PatchErrorKind::TestFailed => HttpResponse::Conflict(),
+                _ => HttpResponse::UnprocessableEntity(),
+            }
+            .json(serde_json::json!({
+                "error": "Patch could not be applied",
+                "details": e.to_string()
+            }));
+            return Err(response);
+        }
+    } else {
+        return Err(HttpResponse::UnsupportedMediaType()
+            .insert_header(("Accept-Patch", format!("{}, {}", MERGE_PATCH, JSON_PATCH)))
+            .json(serde_json::json!({
+                "error": format!("Content-Type must be {} or {}", MERGE_PATCH, JSON_PATCH)
+            })));
+    }
+
+    serde_json::from_value(document).map_err(|e| {
+        HttpResponse::UnprocessableEntity().json(serde_json::json!({
+            "error": "Patched document is invalid",
+            "details": e.to_string()
+        }))
+    })
+}
+
+fn malformed_patch(error: &serde_json::Error) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Malformed patch",
+        "details": error.to_string()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Document {
+        title: String,
+        summary: Option<String>,
+        tags: Vec<String>,
+    }
+
+    fn document() -> Document {
+        Document {
+            title: "Title".to_string(),
+            summary: Some("Summary".to_string()),
+            tags: vec!["rust".to_string()],
+        }
+    }
+
+    fn patch(content_type: &str, body: &str) -> Result<Document, HttpResponse> {
+        let req = TestRequest::default()
+            .insert_header(("Content-Type", content_type))
+            .to_http_request();
+        apply_patch(&req, body.as_bytes(), &document())
+    }
+
+    #[test]
+    fn merge_patches_replace_and_null_out_fields() {
+        let patched = patch(MERGE_PATCH, r#"{"title": "New", "summary": null}"#).unwrap();
+
+        assert_eq!(
+            patched,
+            Document {
+                title: "New".to_string(),
+                summary: None,
+                tags: vec!["rust".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn json_patches_apply_operations_in_order() {
+        let patched = patch(
+            JSON_PATCH,
+            r#"[{"op": "test", "path": "/title", "value": "Title"}, {"op": "add", "path": "/tags/-", "value": "web"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(patched.tags, vec!["rust", "web"]);
+    }
+
+    #[test]
+    fn failures_map_to_their_status_codes() {
+        let status = |result: Result<Document, HttpResponse>| result.unwrap_err().status();
+
+        assert_eq!(status(patch(MERGE_PATCH, "{")), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            status(patch(JSON_PATCH, r#"[{"op": "test", "path": "/title", "value": "Other"}]"#)),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(patch(JSON_PATCH, r#"[{"op": "remove", "path": "/missing"}]"#)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(status(patch(MERGE_PATCH, r#"{"title": 1}"#)), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(status(patch("application/json", "{}")), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
\ No newline at end of file
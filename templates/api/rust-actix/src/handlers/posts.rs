@@ -1,49 +1,176 @@
-use actix_web::{web, HttpRequest, HttpResponse, Result, Scope};
+use actix_web::{
+    http::{
+        header::{ETag, IfMatch, CONTENT_LANGUAGE, LOCATION, USER_AGENT},
+        StatusCode,
+    },
+    web, HttpMessage, HttpRequest, HttpResponse, Result, Scope,
+};
+use sqlx::{types::Json, PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
+    config::Config,
     database::Database,
-    middleware::AuthMiddleware,
-    models::{CreatePostRequest, Post, PostResponse, PostWithUser, UpdatePostRequest},
+    middleware::{viewer_id, AuthMiddleware},
+    models::{
+        next_link, normalize_locale, normalize_tags, resolve_limit, CollaboratorRole, ContentFormat, CreatePostRequest,
+        Cursor, FieldSelection, FieldsQuery, LocaleQuery, Page, Post, PostListQuery, PostResponse, PostSearchQuery, PostSearchResult, PostStatus,
+        PostDocument, PublishPostRequest, ReactionCounts, TrashQuery, UpdatePostRequest, ViewerBookmarks,
+        ViewerReactions,
+    },
+    services::{
+        claim_slug, claim_unique_slug, collaborator_role, post_tags, record_revision, render_content, rerender_translations,
+        set_current_slug, set_post_tags, slug_for_title, BookmarkService, PostColumns, PostService,
+        ReactionService, ViewCounter, Viewer,
+    },
+};
+
+use super::{
+    conditional::{check_if_match, list_etag, post_etag, post_read_etag, precondition_failed, CacheValidators},
+    fields::field_selection,
+    patch::apply_patch,
+    translations::{self, negotiated_translations, requested_locales},
+    attachments, batch, bookmarks, collaborators, comments, moderation, reactions, revisions, stats, transfer,
 };
 
 pub fn config() -> Scope {
     web::scope("/posts")
         .route("", web::get().to(get_posts))
+        .route("/search", web::get().to(search_posts))
+        .route("/by-slug/{slug}", web::get().to(get_post_by_slug))
+        .service(
+            web::resource("/trash")
+                .wrap(AuthMiddleware)
+                .route(web::get().to(get_trash))
+        )
+        .service(batch::post_scope())
+        .service(bookmarks::post_scope())
+        .service(transfer::post_scope())
+        .service(collaborators::shared_posts())
         .route("/{id}", web::get().to(get_post))
+        .service(revisions::config())
+        .service(comments::post_scope())
+        .service(reactions::post_scope())
+        .service(collaborators::post_scope())
+        .service(stats::post_scope())
+        .service(moderation::post_scope())
+        .service(attachments::post_scope())
+        .service(translations::post_scope())
         .service(
             web::scope("")
                 .wrap(AuthMiddleware)
                 .route("", web::post().to(create_post))
                 .route("/{id}", web::put().to(update_post))
+                .route("/{id}", web::patch().to(patch_post))
                 .route("/{id}", web::delete().to(delete_post))
+                .route("/{id}/publish", web::post().to(publish_post))
+                .route("/{id}/unpublish", web::post().to(unpublish_post))
+                .route("/{id}/restore", web::post().to(restore_post))
         )
 }
 
-pub async fn get_posts(db: web::Data<Database>) -> Result<HttpResponse> {
+pub async fn get_posts(
+    req: HttpRequest,
+    query: web::Query<PostListQuery>,
+    fields: web::Query<FieldsQuery>,
+    lang: web::Query<LocaleQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = query.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
+
+    let requested = match requested_locales(&req, &lang) {
+        Ok(requested) => requested,
+        Err(response) => return Ok(response),
+    };
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if query.sort.accepts(query.direction, &cursor) => Some(cursor),
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            })));
+        }
+        None => None,
+    };
+
     let pool = db.get_pool();
 
-    let posts = sqlx::query_as!(
-        PostWithUser,
-        r#"
-        SELECT
-            p.id, p.title, p.content, p.user_id, p.created_at, p.updated_at,
-            u.email as user_email, u.first_name as user_first_name,
-            u.last_name as user_last_name, u.created_at as user_created_at,
-            u.updated_at as user_updated_at
-        FROM posts p
-        JOIN users u ON p.user_id = u.id
-        ORDER BY p.created_at DESC
-        "#
-    )
-    .fetch_all(pool)
+    let viewer = viewer_id(&req).await;
+
+    // Fetch one extra row to find out whether another page follows
+    let posts = async {
+        let posts = PostService::new(pool)
+            .list(&query, viewer, cursor.as_ref(), limit + 1, PostColumns::for_selection(&selection))
+            .await?;
+        let post_ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+        let (reactions, bookmarks) = viewer_state(pool, &selection, viewer, &post_ids).await?;
+        let translations = negotiated_translations(pool, &requested, &posts).await?;
+
+        Ok::<_, sqlx::Error>((posts, reactions, bookmarks, translations))
+    }
     .await;
 
     match posts {
-        Ok(posts) => {
-            let responses: Vec<PostResponse> = posts.into_iter().map(PostResponse::from).collect();
-            Ok(HttpResponse::Ok().json(responses))
+        Ok((mut posts, reactions, bookmarks, mut translations)) => {
+            let has_more = posts.len() as i64 > limit;
+            posts.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                posts.last().map(|post| query.sort.cursor_for(query.direction, post).encode())
+            } else {
+                None
+            };
+
+            let last_modified = posts.iter().map(|post| post.updated_at).max();
+            let page = Page {
+                data: posts
+                    .into_iter()
+                    .map(|post| {
+                        let translation = translations.remove(&post.id);
+                        selection.project(
+                            &PostResponse::from(post)
+                                .with_translation(translation)
+                                .with_viewer_reactions(reactions.as_ref())
+                                .with_viewer_bookmarks(bookmarks.as_ref()),
+                        )
+                    })
+                    .collect(),
+                next_cursor,
+            };
+
+            let validators = CacheValidators::new(list_etag(&page), last_modified).language_negotiated();
+            if validators.is_fresh(&req) {
+                return Ok(validators.not_modified(&config.cache_control_post_list));
+            }
+
+            let mut response = HttpResponse::Ok();
+            validators.apply(&mut response, &config.cache_control_post_list);
+            if let Some(next_cursor) = &page.next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(page))
         }
         Err(e) => {
             log::error!("Database error: {:?}", e);
@@ -54,33 +181,290 @@ pub async fn get_posts(db: web::Data<Database>) -> Result<HttpResponse> {
     }
 }
 
+pub async fn search_posts(
+    req: HttpRequest,
+    query: web::Query<PostSearchQuery>,
+    fields: web::Query<FieldsQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = query.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
+
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    let after = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "rank" => match cursor.key.parse::<f32>() {
+            Ok(rank) => Some((rank, cursor.id)),
+            Err(_) => {
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Invalid cursor"
+                })));
+            }
+        },
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            })));
+        }
+        None => None,
+    };
+
+    let pool = db.get_pool();
+
+    let viewer = viewer_id(&req).await;
+
+    let results = async {
+        let results = PostService::new(pool)
+            .search(
+                &config.search_language,
+                &query.q,
+                viewer,
+                after,
+                limit + 1,
+                PostColumns::for_selection(&selection),
+            )
+            .await?;
+        let post_ids: Vec<Uuid> = results.iter().map(|row| row.post.id).collect();
+        let (reactions, bookmarks) = viewer_state(pool, &selection, viewer, &post_ids).await?;
+
+        Ok::<_, sqlx::Error>((results, reactions, bookmarks))
+    }
+    .await;
+
+    match results {
+        Ok((mut results, reactions, bookmarks)) => {
+            let has_more = results.len() as i64 > limit;
+            results.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                results
+                    .last()
+                    .map(|row| Cursor::new("rank", row.rank.to_string(), row.post.id).encode())
+            } else {
+                None
+            };
+
+            let last_modified = results.iter().map(|row| row.post.updated_at).max();
+            let page = Page {
+                data: results
+                    .into_iter()
+                    .map(|row| {
+                        let mut result = PostSearchResult::from(row);
+                        result.post = result
+                            .post
+                            .with_viewer_reactions(reactions.as_ref())
+                            .with_viewer_bookmarks(bookmarks.as_ref());
+                        selection.project(&result)
+                    })
+                    .collect(),
+                next_cursor,
+            };
+
+            let validators = CacheValidators::new(list_etag(&page), last_modified);
+            if validators.is_fresh(&req) {
+                return Ok(validators.not_modified(&config.cache_control_post_search));
+            }
+
+            let mut response = HttpResponse::Ok();
+            validators.apply(&mut response, &config.cache_control_post_search);
+            if let Some(next_cursor) = &page.next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(page))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to search posts"
+            })))
+        }
+    }
+}
+
 pub async fn get_post(
+    req: HttpRequest,
     path: web::Path<Uuid>,
+    fields: web::Query<FieldsQuery>,
+    lang: web::Query<LocaleQuery>,
     db: web::Data<Database>,
+    config: web::Data<Config>,
+    views: web::Data<ViewCounter>,
 ) -> Result<HttpResponse> {
-    let post_id = path.into_inner();
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
+
+    let requested = match requested_locales(&req, &lang) {
+        Ok(requested) => requested,
+        Err(response) => return Ok(response),
+    };
+
+    read_post(&req, db.get_pool(), &config, &views, &selection, &requested, path.into_inner()).await
+}
+
+/// Looks a post up by its current or a former slug. Former slugs redirect
+/// permanently to the current one.
+pub async fn get_post_by_slug(
+    req: HttpRequest,
+    path: web::Path<String>,
+    fields: web::Query<FieldsQuery>,
+    lang: web::Query<LocaleQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    views: web::Data<ViewCounter>,
+) -> Result<HttpResponse> {
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
+
+    let requested = match requested_locales(&req, &lang) {
+        Ok(requested) => requested,
+        Err(response) => return Ok(response),
+    };
+
+    let slug = path.into_inner();
     let pool = db.get_pool();
+    let viewer = viewer_id(&req).await;
 
-    let post = sqlx::query_as!(
-        PostWithUser,
+    let post = sqlx::query!(
         r#"
-        SELECT
-            p.id, p.title, p.content, p.user_id, p.created_at, p.updated_at,
-            u.email as user_email, u.first_name as user_first_name,
-            u.last_name as user_last_name, u.created_at as user_created_at,
-            u.updated_at as user_updated_at
-        FROM posts p
-        JOIN users u ON p.user_id = u.id
-        WHERE p.id = $1
+        SELECT p.id, p.slug, (p.status = 'published' AND p.hidden_at IS NULL) OR p.user_id = $2 OR EXISTS (
+            SELECT 1 FROM post_collaborators pc WHERE pc.post_id = p.id AND pc.user_id = $2
+        ) as "visible!"
+        FROM post_slugs s
+        JOIN posts p ON p.id = s.post_id
+        WHERE s.slug = $1 AND p.deleted_at IS NULL
         "#,
-        post_id
+        slug,
+        viewer
     )
     .fetch_optional(pool)
     .await;
 
     match post {
-        Ok(Some(post)) => Ok(HttpResponse::Ok().json(PostResponse::from(post))),
-        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+        Ok(Some(post)) if post.visible => {
+            if post.slug == slug {
+                return read_post(&req, pool, &config, &views, &selection, &requested, post.id).await;
+            }
+
+            let location = slug_location(req.path(), req.query_string(), &post.slug);
+            Ok(HttpResponse::MovedPermanently()
+                .insert_header((LOCATION, location))
+                .finish())
+        }
+        Ok(_) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Post not found"
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+/// The request path with its last segment, the requested slug, replaced by
+/// `slug`; the query string is kept.
+fn slug_location(path: &str, query: &str, slug: &str) -> String {
+    let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+    if query.is_empty() {
+        format!("{}/{}", parent, slug)
+    } else {
+        format!("{}/{}?{}", parent, slug, query)
+    }
+}
+
+async fn read_post(
+    req: &HttpRequest,
+    pool: &PgPool,
+    config: &Config,
+    views: &ViewCounter,
+    selection: &FieldSelection,
+    requested: &[String],
+    post_id: Uuid,
+) -> Result<HttpResponse> {
+    let viewer = viewer_id(req).await;
+
+    // Collaborators decide who may see an unpublished post
+    let columns = PostColumns {
+        collaborators: true,
+        ..PostColumns::for_selection(selection)
+    };
+
+    let post = async {
+        let Some(post) = PostService::new(pool).find_selected(post_id, columns).await? else {
+            return Ok(None);
+        };
+        let (reactions, bookmarks) = viewer_state(pool, selection, viewer, &[post_id]).await?;
+        let mut translations = negotiated_translations(pool, requested, std::slice::from_ref(&post)).await?;
+
+        Ok::<_, sqlx::Error>(Some((post, reactions, bookmarks, translations.remove(&post_id))))
+    }
+    .await;
+
+    match post {
+        Ok(Some((post, reactions, bookmarks, translation))) if post.is_visible_to(viewer) => {
+            // Authors reading their own posts are not counted
+            match viewer {
+                Some(user_id) if user_id == post.user_id => {}
+                Some(user_id) => views.record(post.id, Viewer::User(user_id)),
+                None => {
+                    let user_agent = req
+                        .headers()
+                        .get(USER_AGENT)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default();
+                    // Forwarded headers are client-controlled unless a proxy sets them
+                    let address = if config.trust_proxy_headers {
+                        req.connection_info().realip_remote_addr().map(str::to_string)
+                    } else {
+                        req.peer_addr().map(|addr| addr.ip().to_string())
+                    }
+                    .unwrap_or_default();
+                    views.record(post.id, Viewer::anonymous(&address, user_agent));
+                }
+            }
+
+            let updated_at = post.updated_at;
+            let post = PostResponse::from(post)
+                .with_translation(translation)
+                .with_viewer_reactions(reactions.as_ref())
+                .with_viewer_bookmarks(bookmarks.as_ref());
+            let body = selection.project(&post);
+
+            let validators =
+                CacheValidators::new(post_read_etag(updated_at, &body), Some(updated_at)).language_negotiated();
+            if validators.is_fresh(req) {
+                return Ok(validators.not_modified(&config.cache_control_post));
+            }
+
+            let mut response = HttpResponse::Ok();
+            validators.apply(&mut response, &config.cache_control_post);
+            response.insert_header((CONTENT_LANGUAGE, post.locale.clone()));
+            Ok(response.json(body))
+        }
+        Ok(_) => Ok(HttpResponse::NotFound().json(serde_json::json!({
             "error": "Post not found"
         }))),
         Err(e) => {
@@ -92,10 +476,31 @@ pub async fn get_post(
     }
 }
 
+/// The viewer's reactions and bookmarks among `post_ids`, skipping the
+/// lookups `selection` leaves out of the response.
+async fn viewer_state(
+    pool: &PgPool,
+    selection: &FieldSelection,
+    viewer: Option<Uuid>,
+    post_ids: &[Uuid],
+) -> Result<(Option<ViewerReactions>, Option<ViewerBookmarks>), sqlx::Error> {
+    let reactions = match viewer.filter(|_| selection.includes("viewer_reactions")) {
+        Some(viewer) => ReactionService::new(pool).for_viewer(Some(viewer), post_ids).await?,
+        None => None,
+    };
+    let bookmarks = match viewer.filter(|_| selection.includes("bookmarked")) {
+        Some(viewer) => BookmarkService::new(pool).for_viewer(Some(viewer), post_ids).await?,
+        None => None,
+    };
+
+    Ok((reactions, bookmarks))
+}
+
 pub async fn create_post(
     req: HttpRequest,
     body: web::Json<CreatePostRequest>,
     db: web::Data<Database>,
+    config: web::Data<Config>,
 ) -> Result<HttpResponse> {
     if let Err(errors) = body.validate() {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
@@ -115,48 +520,19 @@ pub async fn create_post(
 
     let pool = db.get_pool();
 
-    let post = sqlx::query_as!(
-        Post,
-        r#"
-        INSERT INTO posts (title, content, user_id)
-        VALUES ($1, $2, $3)
-        RETURNING *
-        "#,
-        body.title,
-        body.content,
-        user_id
-    )
-    .fetch_one(pool)
+    let post = async {
+        let mut tx = pool.begin().await?;
+        let post = insert_post(&mut tx, &config, user_id, &body).await?;
+        if post.is_ok() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(post)
+    }
     .await;
 
     match post {
-        Ok(post) => {
-            // Fetch the post with user information
-            let post_with_user = sqlx::query_as!(
-                PostWithUser,
-                r#"
-                SELECT
-                    p.id, p.title, p.content, p.user_id, p.created_at, p.updated_at,
-                    u.email as user_email, u.first_name as user_first_name,
-                    u.last_name as user_last_name, u.created_at as user_created_at,
-                    u.updated_at as user_updated_at
-                FROM posts p
-                JOIN users u ON p.user_id = u.id
-                WHERE p.id = $1
-                "#,
-                post.id
-            )
-            .fetch_one(pool)
-            .await;
-
-            match post_with_user {
-                Ok(post_with_user) => Ok(HttpResponse::Created().json(PostResponse::from(post_with_user))),
-                Err(e) => {
-                    log::error!("Database error fetching created post: {:?}", e);
-                    Ok(HttpResponse::Created().json(PostResponse::from(post)))
-                }
-            }
-        }
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::CREATED).await),
         Err(e) => {
             log::error!("Failed to create post: {:?}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -171,6 +547,7 @@ pub async fn update_post(
     path: web::Path<Uuid>,
     body: web::Json<UpdatePostRequest>,
     db: web::Data<Database>,
+    config: web::Data<Config>,
 ) -> Result<HttpResponse> {
     if let Err(errors) = body.validate() {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
@@ -191,96 +568,466 @@ pub async fn update_post(
     let post_id = path.into_inner();
     let pool = db.get_pool();
 
-    // Check if post exists and user owns it
-    let existing_post = sqlx::query_as!(
-        Post,
-        "SELECT * FROM posts WHERE id = $1",
-        post_id
-    )
-    .fetch_optional(pool)
+    let updated_post = async {
+        let mut tx = pool.begin().await?;
+        let post = apply_update(&mut tx, &config, user_id, post_id, &body, req.get_header::<IfMatch>()).await?;
+        if post.is_ok() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(post)
+    }
     .await;
 
-    let existing_post = match existing_post {
-        Ok(Some(post)) => post,
-        Ok(None) => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Post not found"
+    match updated_post {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::OK).await),
+        Err(e) => {
+            log::error!("Failed to update post: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update post"
+            })))
+        }
+    }
+}
+
+/// Applies a JSON Merge Patch or JSON Patch to the post's editable fields
+/// (see [`PostDocument`]), then validates and writes the result like `PUT`.
+pub async fn patch_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Bytes,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
             })));
         }
+    };
+
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    let patched_post = async {
+        let mut tx = pool.begin().await?;
+
+        // The post stays locked until commit, so the patch applies to what
+        // gets written
+        let post = match find_writable_post(&mut tx, user_id, post_id, PostWrite::Update).await? {
+            Ok(post) => post,
+            Err(response) => return Ok(Err(response)),
+        };
+        let original = PostDocument::new(&post, post_tags(&mut tx, post_id).await?);
+
+        let update = match apply_patch(&req, &body, &original) {
+            Ok(document) => document.changes_from(&original),
+            Err(response) => return Ok(Err(response)),
+        };
+        if let Err(errors) = update.validate() {
+            return Ok(Err(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Validation failed",
+                "details": errors
+            }))));
+        }
+
+        let post = apply_update(&mut tx, &config, user_id, post_id, &update, req.get_header::<IfMatch>()).await?;
+        if post.is_ok() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(post)
+    }
+    .await;
+
+    match patched_post {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::OK).await),
         Err(e) => {
-            log::error!("Database error: {:?}", e);
-            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Database error"
+            log::error!("Failed to patch post: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update post"
+            })))
+        }
+    }
+}
+
+/// Response to a successful write, with user information when the post
+/// can be fetched again.
+pub(super) async fn written_post(pool: &PgPool, post: Post, status: StatusCode) -> HttpResponse {
+    match PostService::new(pool).find_with_user(post.id).await {
+        Ok(Some(post_with_user)) => HttpResponse::build(status)
+            .insert_header(ETag(post_etag(post_with_user.updated_at)))
+            .json(PostResponse::from(post_with_user)),
+        result => {
+            if let Err(e) = result {
+                log::error!("Database error fetching written post: {:?}", e);
+            }
+            HttpResponse::build(status)
+                .insert_header(ETag(post_etag(post.updated_at)))
+                .json(PostResponse::from(post))
+        }
+    }
+}
+
+pub async fn delete_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
             })));
         }
     };
 
-    if existing_post.user_id != user_id {
-        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "You can only update your own posts"
-        })));
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let result = trash_post(&mut tx, &config, user_id, post_id, req.get_header::<IfMatch>()).await?;
+        if result.is_ok() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(result)
+    }
+    .await;
+
+    match result {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(())) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => {
+            log::error!("Failed to delete post: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete post"
+            })))
+        }
     }
+}
+
+/// Creates a post together with its slug, first revision and tags. `body`
+/// must already be validated. Requests the client has to fix come back as
+/// the response to send, leaving the transaction to be rolled back.
+pub(super) async fn insert_post(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: Uuid,
+    body: &CreatePostRequest,
+) -> Result<Result<Post, HttpResponse>, sqlx::Error> {
+    let (status, publish_at) = body.publication();
+    let rendered = render_content(body.content_format, &body.content);
+    let locale = body
+        .locale
+        .as_deref()
+        .and_then(normalize_locale)
+        .unwrap_or_else(|| config.default_locale.clone());
+
+    let post_id = Uuid::new_v4();
+    let slug = match &body.slug {
+        Some(slug) if claim_slug(conn, post_id, slug).await? => slug.clone(),
+        Some(_) => return Ok(Err(slug_conflict())),
+        None => claim_unique_slug(conn, post_id, &slug_for_title(&body.title)).await?,
+    };
+
+    let post = sqlx::query_as!(
+        Post,
+        r#"
+        INSERT INTO posts (
+            id, title, slug, custom_slug, content, content_format, content_html, excerpt,
+            reading_time_minutes, locale, user_id, status, publish_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale
+        "#,
+        post_id,
+        body.title,
+        slug,
+        body.slug.is_some(),
+        body.content,
+        body.content_format as ContentFormat,
+        rendered.html,
+        rendered.excerpt,
+        rendered.reading_time_minutes,
+        locale,
+        user_id,
+        status as PostStatus,
+        publish_at
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    record_revision(conn, post.id, &post.title, &post.content, post.content_format, user_id).await?;
+    if let Some(tags) = &body.tags {
+        set_post_tags(conn, post.id, &normalize_tags(tags)).await?;
+    }
+
+    Ok(Ok(post))
+}
+
+/// Applies a validated update to a post `user_id` owns or edits and records
+/// the new revision, like [`insert_post`] handing back refusals as responses.
+pub(super) async fn apply_update(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: Uuid,
+    post_id: Uuid,
+    body: &UpdatePostRequest,
+    if_match: Option<IfMatch>,
+) -> Result<Result<Post, HttpResponse>, sqlx::Error> {
+    let existing_post = match find_writable_post(conn, user_id, post_id, PostWrite::Update).await? {
+        Ok(post) => post,
+        Err(response) => return Ok(Err(response)),
+    };
+
+    let expected_versions = match check_if_match(if_match, config, existing_post.updated_at) {
+        Ok(versions) => versions,
+        Err(response) => return Ok(Err(response)),
+    };
 
-    // Update post
     let updated_title = body.title.as_ref().unwrap_or(&existing_post.title);
     let updated_content = body.content.as_ref().unwrap_or(&existing_post.content);
+    let updated_format = body.content_format.unwrap_or(existing_post.content_format);
+    let rendered = render_content(updated_format, updated_content);
 
-    let updated_post = sqlx::query_as!(
+    let post = sqlx::query_as!(
         Post,
         r#"
         UPDATE posts
-        SET title = $1, content = $2, updated_at = NOW()
-        WHERE id = $3
-        RETURNING *
+        SET title = $1, content = $2, content_format = $3, content_html = $4, excerpt = $5,
+            reading_time_minutes = $6, updated_at = NOW()
+        WHERE id = $7 AND ($8::timestamptz[] IS NULL OR updated_at = ANY($8))
+        RETURNING id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale
         "#,
         updated_title,
         updated_content,
+        updated_format as ContentFormat,
+        rendered.html,
+        rendered.excerpt,
+        rendered.reading_time_minutes,
+        post_id,
+        expected_versions.as_deref()
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    // A concurrent write got in first; nothing was changed
+    let Some(mut post) = post else {
+        return Ok(Err(precondition_failed()));
+    };
+
+    if post.content_format != existing_post.content_format {
+        rerender_translations(conn, post.id, post.content_format).await?;
+    }
+
+    // Generated slugs follow the title; custom ones stay until replaced
+    if let Some(slug) = &body.slug {
+        if !claim_slug(conn, post.id, slug).await? {
+            return Ok(Err(slug_conflict()));
+        }
+        set_current_slug(conn, post.id, slug, true).await?;
+        post.slug = slug.clone();
+    } else if !post.custom_slug && post.title != existing_post.title {
+        let slug = claim_unique_slug(conn, post.id, &slug_for_title(&post.title)).await?;
+        set_current_slug(conn, post.id, &slug, false).await?;
+        post.slug = slug;
+    }
+
+    record_revision(conn, post.id, &post.title, &post.content, post.content_format, user_id).await?;
+    if let Some(tags) = &body.tags {
+        set_post_tags(conn, post.id, &normalize_tags(tags)).await?;
+    }
+
+    Ok(Ok(post))
+}
+
+/// Moves one of `user_id`'s posts to the trash.
+pub(super) async fn trash_post(
+    conn: &mut PgConnection,
+    config: &Config,
+    user_id: Uuid,
+    post_id: Uuid,
+    if_match: Option<IfMatch>,
+) -> Result<Result<(), HttpResponse>, sqlx::Error> {
+    let existing_post = match find_writable_post(conn, user_id, post_id, PostWrite::Delete).await? {
+        Ok(post) => post,
+        Err(response) => return Ok(Err(response)),
+    };
+
+    let expected_versions = match check_if_match(if_match, config, existing_post.updated_at) {
+        Ok(versions) => versions,
+        Err(response) => return Ok(Err(response)),
+    };
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE posts SET deleted_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
+          AND ($2::timestamptz[] IS NULL OR updated_at = ANY($2))
+        "#,
+        post_id,
+        expected_versions.as_deref()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(Err(precondition_failed()));
+    }
+
+    Ok(Ok(()))
+}
+
+/// Writes that go through [`find_writable_post`].
+#[derive(Debug, Clone, Copy)]
+pub(super) enum PostWrite {
+    /// Owners and editors may update a post.
+    Update,
+    /// Only owners may delete one.
+    Delete,
+    /// Only owners may publish, schedule or unpublish one.
+    Publish,
+}
+
+/// Fetches and locks a post that is not in the trash for a write by
+/// `user_id`, who must own it or, for updates, have been invited to edit it.
+pub(super) async fn find_writable_post(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    post_id: Uuid,
+    write: PostWrite,
+) -> Result<Result<Post, HttpResponse>, sqlx::Error> {
+    let post = sqlx::query_as!(
+        Post,
+        r#"SELECT id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale FROM posts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#,
         post_id
     )
-    .fetch_one(pool)
-    .await;
+    .fetch_optional(&mut *conn)
+    .await?;
 
-    match updated_post {
-        Ok(post) => {
-            // Fetch the post with user information
-            let post_with_user = sqlx::query_as!(
-                PostWithUser,
-                r#"
-                SELECT
-                    p.id, p.title, p.content, p.user_id, p.created_at, p.updated_at,
-                    u.email as user_email, u.first_name as user_first_name,
-                    u.last_name as user_last_name, u.created_at as user_created_at,
-                    u.updated_at as user_updated_at
-                FROM posts p
-                JOIN users u ON p.user_id = u.id
-                WHERE p.id = $1
-                "#,
-                post.id
-            )
-            .fetch_one(pool)
-            .await;
-
-            match post_with_user {
-                Ok(post_with_user) => Ok(HttpResponse::Ok().json(PostResponse::from(post_with_user))),
-                Err(e) => {
-                    log::error!("Database error fetching updated post: {:?}", e);
-                    Ok(HttpResponse::Ok().json(PostResponse::from(post)))
-                }
+    let Some(post) = post else {
+        return Ok(Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Post not found"
+        }))));
+    };
+    if post.user_id == user_id {
+        return Ok(Ok(post));
+    }
+
+    let message = match write {
+        PostWrite::Update => {
+            if collaborator_role(conn, post_id, user_id).await? == Some(CollaboratorRole::Editor) {
+                return Ok(Ok(post));
             }
+            "You can only update posts you own or were invited to edit"
+        }
+        PostWrite::Delete => "You can only delete your own posts",
+        PostWrite::Publish => "You can only publish your own posts",
+    };
+    Ok(Err(HttpResponse::Forbidden().json(serde_json::json!({
+        "error": message
+    }))))
+}
+
+pub async fn publish_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: Option<web::Json<PublishPostRequest>>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let publish_at = body.and_then(|body| body.into_inner().publish_at);
+
+    let (status, publish_at) = match publish_at {
+        Some(publish_at) if publish_at > chrono::Utc::now() => (PostStatus::Scheduled, publish_at),
+        _ => (PostStatus::Published, chrono::Utc::now()),
+    };
+
+    set_publication(req, path.into_inner(), status, Some(publish_at), db, config).await
+}
+
+pub async fn unpublish_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    set_publication(req, path.into_inner(), PostStatus::Draft, None, db, config).await
+}
+
+async fn set_publication(
+    req: HttpRequest,
+    post_id: Uuid,
+    status: PostStatus,
+    publish_at: Option<chrono::DateTime<chrono::Utc>>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let pool = db.get_pool();
+
+    let updated_post = async {
+        let mut tx = pool.begin().await?;
+
+        let existing_post = match find_writable_post(&mut tx, user_id, post_id, PostWrite::Publish).await? {
+            Ok(post) => post,
+            Err(response) => return Ok(Err(response)),
+        };
+        if let Err(response) = check_if_match(req.get_header::<IfMatch>(), &config, existing_post.updated_at) {
+            return Ok(Err(response));
         }
+
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            UPDATE posts
+            SET status = $1, publish_at = $2, updated_at = NOW()
+            WHERE id = $3 AND deleted_at IS NULL
+            RETURNING id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale
+            "#,
+            status as PostStatus,
+            publish_at,
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(post))
+    }
+    .await;
+
+    match updated_post {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::OK).await),
         Err(e) => {
-            log::error!("Failed to update post: {:?}", e);
+            log::error!("Failed to update post status: {:?}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to update post"
+                "error": "Failed to update post status"
             })))
         }
     }
 }
 
-pub async fn delete_post(
+pub async fn get_trash(
     req: HttpRequest,
-    path: web::Path<Uuid>,
+    query: web::Query<TrashQuery>,
+    fields: web::Query<FieldsQuery>,
     db: web::Data<Database>,
+    config: web::Data<Config>,
 ) -> Result<HttpResponse> {
     let user_id = match req.extensions().get::<Uuid>() {
         Some(id) => *id,
@@ -291,54 +1038,163 @@ pub async fn delete_post(
         }
     };
 
-    let post_id = path.into_inner();
-    let pool = db.get_pool();
+    let selection = match field_selection::<PostResponse>(&fields) {
+        Ok(selection) => selection,
+        Err(response) => return Ok(response),
+    };
 
-    // Check if post exists and user owns it
-    let existing_post = sqlx::query_as!(
-        Post,
-        "SELECT * FROM posts WHERE id = $1",
-        post_id
-    )
-    .fetch_optional(pool)
-    .await;
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
 
-    let existing_post = match existing_post {
-        Ok(Some(post)) => post,
-        Ok(None) => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Post not found"
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        Some(Some(cursor)) if cursor.sort == "deleted_at" && cursor.timestamp().is_some() => Some(cursor),
+        Some(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
             })));
         }
+        None => None,
+    };
+
+    let pool = db.get_pool();
+
+    let posts = PostService::new(pool)
+        .list_trash(user_id, cursor.as_ref(), limit + 1, PostColumns::for_selection(&selection))
+        .await;
+
+    match posts {
+        Ok(mut posts) => {
+            let has_more = posts.len() as i64 > limit;
+            posts.truncate(limit as usize);
+
+            let next_cursor = if has_more {
+                posts.last().and_then(|post| {
+                    post.deleted_at
+                        .map(|deleted_at| Cursor::for_timestamp("deleted_at", deleted_at, post.id).encode())
+                })
+            } else {
+                None
+            };
+
+            let mut response = HttpResponse::Ok();
+            if let Some(next_cursor) = &next_cursor {
+                response.insert_header(("Link", next_link(&req, next_cursor)));
+            }
+
+            Ok(response.json(Page {
+                data: posts
+                    .into_iter()
+                    .map(|post| selection.project(&PostResponse::from(post)))
+                    .collect(),
+                next_cursor,
+            }))
+        }
         Err(e) => {
             log::error!("Database error: {:?}", e);
-            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Database error"
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch trash"
+            })))
+        }
+    }
+}
+
+pub async fn restore_post(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
             })));
         }
     };
 
-    if existing_post.user_id != user_id {
-        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "You can only delete your own posts"
-        })));
-    }
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
 
-    // Delete post
-    let result = sqlx::query!(
-        "DELETE FROM posts WHERE id = $1",
-        post_id
-    )
-    .execute(pool)
+    // Lock the trashed post so that the purger cannot delete it in between
+    let restored_post = async {
+        let mut tx = pool.begin().await?;
+
+        let owner_id = sqlx::query_scalar!(
+            "SELECT user_id FROM posts WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE",
+            post_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match owner_id {
+            Some(owner_id) if owner_id == user_id => {}
+            Some(_) => {
+                return Ok(Err(HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "You can only restore your own posts"
+                }))));
+            }
+            None => {
+                return Ok(Err(HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Post not found in trash"
+                }))));
+            }
+        }
+
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            UPDATE posts
+            SET deleted_at = NULL
+            WHERE id = $1
+            RETURNING id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale
+            "#,
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(post))
+    }
     .await;
 
-    match result {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+    match restored_post {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::OK).await),
         Err(e) => {
-            log::error!("Failed to delete post: {:?}", e);
+            log::error!("Failed to restore post: {:?}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete post"
+                "error": "Failed to restore post"
             })))
         }
     }
+}
+
+fn slug_conflict() -> HttpResponse {
+    HttpResponse::Conflict().json(serde_json::json!({
+        "error": "Slug is already in use by another post"
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_location_only_replaces_the_last_segment() {
+        assert_eq!(
+            slug_location("/api/v1/posts/by-slug/post", "", "new-post"),
+            "/api/v1/posts/by-slug/new-post"
+        );
+        assert_eq!(
+            slug_location("/api/v1/posts/by-slug/v1", "fields=title", "posts"),
+            "/api/v1/posts/by-slug/posts?fields=title"
+        );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    services::{PostService, ReactionService},
+};
+
+/// Reaction routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::scope("/{id}/reactions")
+        .wrap(AuthMiddleware)
+        .route("/{kind}", web::put().to(add_reaction))
+        .route("/{kind}", web::delete().to(remove_reaction))
+}
+
+pub async fn add_reaction(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    set_reaction(req, path, db, config, true).await
+}
+
+pub async fn remove_reaction(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    set_reaction(req, path, db, config, false).await
+}
+
+async fn set_reaction(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    reacted: bool,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let (post_id, kind) = path.into_inner();
+
+    if !config.reaction_kinds.contains(&kind) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown reaction kind '{}'", kind),
+            "allowed": config.reaction_kinds
+        })));
+    }
+
+    let pool = db.get_pool();
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.is_visible_to(Some(user_id)) => {}
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    let service = ReactionService::new(pool);
+    let summary = if reacted {
+        service.react(post_id, user_id, &kind).await
+    } else {
+        service.unreact(post_id, user_id, &kind).await
+    };
+
+    match summary {
+        Ok(summary) => Ok(HttpResponse::Ok().json(summary)),
+        Err(e) => {
+            log::error!("Failed to update reaction: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update reaction"
+            })))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+use actix_web::{
+    dev::HttpServiceFactory,
+    http::{header::IfMatch, StatusCode},
+    web, HttpMessage, HttpRequest, HttpResponse, Result,
+};
+use sqlx::{types::Json, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{
+        ContentFormat, Post, PostRevision, PostRevisionSummary, PostStatus, ReactionCounts,
+        RevisionDiffQuery,
+    },
+    services::{
+        claim_unique_slug, diff_revisions, record_revision, render_content, rerender_translations,
+        set_current_slug, slug_for_title,
+    },
+};
+
+use super::{
+    conditional::check_if_match,
+    posts::{find_writable_post, written_post, PostWrite},
+};
+
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/{id}/revisions")
+        .wrap(AuthMiddleware)
+        .route("", web::get().to(get_revisions))
+        .route("/diff", web::get().to(diff_post_revisions))
+        .route("/{revision}", web::get().to(get_revision))
+        .route("/{revision}/restore", web::post().to(restore_revision))
+}
+
+pub async fn get_revisions(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    if let Err(response) = find_owned_post(&req, pool, post_id).await {
+        return Ok(response);
+    }
+
+    let revisions = sqlx::query_as!(
+        PostRevisionSummary,
+        r#"
+        SELECT revision, title, editor_id, created_at
+        FROM post_revisions
+        WHERE post_id = $1
+        ORDER BY revision DESC
+        "#,
+        post_id
+    )
+    .fetch_all(pool)
+    .await;
+
+    match revisions {
+        Ok(revisions) => Ok(HttpResponse::Ok().json(revisions)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch revisions"
+            })))
+        }
+    }
+}
+
+pub async fn get_revision(
+    req: HttpRequest,
+    path: web::Path<(Uuid, i32)>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let (post_id, revision) = path.into_inner();
+    let pool = db.get_pool();
+
+    if let Err(response) = find_owned_post(&req, pool, post_id).await {
+        return Ok(response);
+    }
+
+    match fetch_revision(pool, post_id, revision).await {
+        Ok(revision) => Ok(HttpResponse::Ok().json(revision)),
+        Err(response) => Ok(response),
+    }
+}
+
+pub async fn diff_post_revisions(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<RevisionDiffQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    if let Err(response) = find_owned_post(&req, pool, post_id).await {
+        return Ok(response);
+    }
+
+    let from = match fetch_revision(pool, post_id, query.from).await {
+        Ok(revision) => revision,
+        Err(response) => return Ok(response),
+    };
+    let to = match fetch_revision(pool, post_id, query.to).await {
+        Ok(revision) => revision,
+        Err(response) => return Ok(response),
+    };
+
+    Ok(HttpResponse::Ok().json(diff_revisions(&from, &to)))
+}
+
+pub async fn restore_revision(
+    req: HttpRequest,
+    path: web::Path<(Uuid, i32)>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let (post_id, revision) = path.into_inner();
+    let pool = db.get_pool();
+
+    // Restoring is an edit of its own, so it gets a new revision
+    let restored_post = async {
+        let mut tx = pool.begin().await?;
+
+        let existing_post = match find_writable_post(&mut tx, user_id, post_id, PostWrite::Update).await? {
+            Ok(post) => post,
+            Err(response) => return Ok(Err(response)),
+        };
+        if let Err(response) = check_if_match(req.get_header::<IfMatch>(), &config, existing_post.updated_at) {
+            return Ok(Err(response));
+        }
+        let revision = match fetch_revision(&mut *tx, post_id, revision).await {
+            Ok(revision) => revision,
+            Err(response) => return Ok(Err(response)),
+        };
+
+        let rendered = render_content(revision.content_format, &revision.content);
+
+        let mut post = sqlx::query_as!(
+            Post,
+            r#"
+            UPDATE posts
+            SET title = $1, content = $2, content_format = $3, content_html = $4, excerpt = $5,
+                reading_time_minutes = $6, updated_at = NOW()
+            WHERE id = $7 AND deleted_at IS NULL
+            RETURNING id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale
+            "#,
+            revision.title,
+            revision.content,
+            revision.content_format as ContentFormat,
+            rendered.html,
+            rendered.excerpt,
+            rendered.reading_time_minutes,
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if post.content_format != existing_post.content_format {
+            rerender_translations(&mut tx, post.id, post.content_format).await?;
+        }
+
+        if !post.custom_slug && post.title != existing_post.title {
+            let slug = claim_unique_slug(&mut tx, post.id, &slug_for_title(&post.title)).await?;
+            set_current_slug(&mut tx, post.id, &slug, false).await?;
+            post.slug = slug;
+        }
+
+        record_revision(&mut tx, post.id, &post.title, &post.content, post.content_format, user_id).await?;
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(Ok(post))
+    }
+    .await;
+
+    match restored_post {
+        Ok(Err(response)) => Ok(response),
+        Ok(Ok(post)) => Ok(written_post(pool, post, StatusCode::OK).await),
+        Err(e) => {
+            log::error!("Failed to restore revision: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to restore revision"
+            })))
+        }
+    }
+}
+
+/// Loads a live post and checks that the authenticated user owns it,
+/// producing the error response to return otherwise.
+async fn find_owned_post(
+    req: &HttpRequest,
+    pool: &PgPool,
+    post_id: Uuid,
+) -> Result<Post, HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Err(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let post = sqlx::query_as!(
+        Post,
+        r#"SELECT id, title, slug, custom_slug, content, content_format as "content_format: ContentFormat", content_html, excerpt, reading_time_minutes, user_id, status as "status: PostStatus", publish_at, comment_count, reaction_counts as "reaction_counts: Json<ReactionCounts>", created_at, updated_at, deleted_at, locale FROM posts WHERE id = $1 AND deleted_at IS NULL"#,
+        post_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match post {
+        Ok(Some(post)) if post.user_id == user_id => Ok(post),
+        Ok(Some(_)) => Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "You can only view the history of your own posts"
+        }))),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Post not found"
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+async fn fetch_revision<'e>(
+    executor: impl PgExecutor<'e>,
+    post_id: Uuid,
+    revision: i32,
+) -> Result<PostRevision, HttpResponse> {
+    let result = sqlx::query_as!(
+        PostRevision,
+        r#"
+        SELECT id, post_id, revision, title, content, content_format as "content_format: ContentFormat", editor_id, created_at
+        FROM post_revisions
+        WHERE post_id = $1 AND revision = $2
+        "#,
+        post_id,
+        revision
+    )
+    .fetch_optional(executor)
+    .await;
+
+    match result {
+        Ok(Some(revision)) => Ok(revision),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Revision {} not found", revision)
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use chrono::{Days, Utc};
+use uuid::Uuid;
+
+use crate::{
+    database::Database,
+    middleware::AuthMiddleware,
+    models::PostStatsQuery,
+    services::{PostService, ViewService},
+};
+
+/// Default length of the stats range, in days.
+const DEFAULT_RANGE_DAYS: u64 = 30;
+/// Longest stats range, in days.
+const MAX_RANGE_DAYS: u64 = 366;
+
+/// Stats routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::resource("/{id}/stats")
+        .wrap(AuthMiddleware)
+        .route(web::get().to(get_post_stats))
+}
+
+/// Daily view counts of one of the current user's posts. Views are
+/// buffered, so the latest ones show up after the next flush.
+pub async fn get_post_stats(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<PostStatsQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query
+        .from
+        .or_else(|| to.checked_sub_days(Days::new(DEFAULT_RANGE_DAYS - 1)))
+        .unwrap_or(to);
+    if from > to {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "from must not be after to"
+        })));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS as i64 {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("The range can span at most {} days", MAX_RANGE_DAYS)
+        })));
+    }
+
+    let pool = db.get_pool();
+    let post_id = path.into_inner();
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.user_id == user_id => {}
+        Ok(Some(post)) if post.is_visible_to(Some(user_id)) => {
+            return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "You can only view the stats of your own posts"
+            })));
+        }
+        Ok(_) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Post not found"
+            })));
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    }
+
+    match ViewService::new(pool).stats(post_id, from, to).await {
+        Ok(stats) => Ok(HttpResponse::Ok().json(stats)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch post stats"
+            })))
+        }
+    }
+}
\ No newline at end of file
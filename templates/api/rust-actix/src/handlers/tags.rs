@@ -0,0 +1,38 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+
+use crate::{
+    config::Config,
+    database::Database,
+    models::{resolve_limit, TagListQuery},
+    services::TagService,
+};
+
+pub fn config() -> Scope {
+    web::scope("/tags")
+        .route("", web::get().to(get_tags))
+}
+
+pub async fn get_tags(
+    query: web::Query<TagListQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let limit = match resolve_limit(query.limit, config.default_page_size, config.max_page_size) {
+        Ok(limit) => limit,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })));
+        }
+    };
+
+    match TagService::new(db.get_pool()).list_counts(limit).await {
+        Ok(tags) => Ok(HttpResponse::Ok().json(tags)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch tags"
+            })))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+
+use actix_web::{
+    body::to_bytes,
+    dev::HttpServiceFactory,
+    http::header::{ContentDisposition, DispositionParam, DispositionType, CONTENT_TYPE},
+    web, HttpMessage, HttpRequest, HttpResponse, Result,
+};
+use futures_util::StreamExt;
+use sqlx::{Acquire, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{CreatePostRequest, ExportQuery, ImportError, ImportSummary, ImportedPost, PostStatus},
+    services::{export_posts, restore_timestamps, TransferService},
+};
+
+use super::posts::insert_post;
+
+/// Media type `POST /posts/import` accepts.
+pub const NDJSON: &str = "application/x-ndjson";
+
+/// Longest line an import accepts.
+const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// How many failed lines an import summary lists.
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// `GET /posts/export` and `POST /posts/import`, registered by the posts scope.
+pub fn post_scope() -> impl HttpServiceFactory {
+    (
+        web::resource("/export")
+            .wrap(AuthMiddleware)
+            .route(web::get().to(export)),
+        web::resource("/import")
+            .wrap(AuthMiddleware)
+            .route(web::post().to(import)),
+    )
+}
+
+/// Streams the current user's posts, or every post for admins, as NDJSON
+/// or CSV (`?format=csv`).
+pub async fn export(
+    req: HttpRequest,
+    query: web::Query<ExportQuery>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let pool = db.get_pool();
+
+    let owner = match TransferService::new(pool).is_admin(user_id).await {
+        Ok(true) => None,
+        Ok(false) => Some(user_id),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    let format = query.format;
+    let body = export_posts(pool.clone(), owner, format, config.export_batch_size).inspect(|chunk| {
+        if let Err(e) = chunk {
+            log::error!("Post export failed: {:?}", e);
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .insert_header((CONTENT_TYPE, format.content_type()))
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("posts.{}", format.extension()))],
+        })
+        .streaming(body))
+}
+
+/// Creates posts from an NDJSON body, one post per line, as the current
+/// user. Admins may set `author_email` to import on behalf of others.
+///
+/// Lines are validated like `POST /posts` and written `IMPORT_BATCH_SIZE`
+/// at a time; a refused line is reported and skipped without affecting the
+/// others.
+pub async fn import(
+    req: HttpRequest,
+    mut payload: web::Payload,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    if !req.content_type().eq_ignore_ascii_case(NDJSON) {
+        return Ok(HttpResponse::UnsupportedMediaType().json(serde_json::json!({
+            "error": format!("Content-Type must be {}", NDJSON)
+        })));
+    }
+
+    let pool = db.get_pool();
+
+    let is_admin = match TransferService::new(pool).is_admin(user_id).await {
+        Ok(is_admin) => is_admin,
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })));
+        }
+    };
+
+    let mut importer = Importer::new(pool, &config, user_id, is_admin, config.import_batch_size.max(1));
+    let mut lines = LineSplitter::new(MAX_LINE_BYTES);
+
+    let outcome = async {
+        while let Some(chunk) = payload.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    log::warn!("Import body interrupted: {:?}", e);
+                    return Ok(false);
+                }
+            };
+            for line in lines.push(&chunk) {
+                importer.add(line).await?;
+            }
+        }
+        if let Some(line) = lines.finish() {
+            importer.add(line).await?;
+        }
+        importer.flush().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match outcome {
+        Ok(true) => Ok(HttpResponse::Ok().json(importer.summary)),
+        // Batches written before the body broke off stay imported
+        Ok(false) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Failed to read request body",
+            "imported": importer.summary.imported
+        }))),
+        Err(e) => {
+            log::error!("Failed to import posts: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to import posts",
+                "imported": importer.summary.imported
+            })))
+        }
+    }
+}
+
+/// A line of an import body.
+enum Line {
+    Text(usize, Vec<u8>),
+    /// Longer than the limit; its bytes were dropped.
+    TooLong(usize),
+}
+
+/// Splits a streamed body into lines, holding at most one line in memory.
+struct LineSplitter {
+    buffer: Vec<u8>,
+    max_len: usize,
+    number: usize,
+    overlong: bool,
+}
+
+impl LineSplitter {
+    fn new(max_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_len,
+            number: 0,
+            overlong: false,
+        }
+    }
+
+    /// Lines completed by `chunk`.
+    fn push(&mut self, mut chunk: &[u8]) -> Vec<Line> {
+        let mut lines = Vec::new();
+
+        while let Some(end) = chunk.iter().position(|&byte| byte == b'\n') {
+            self.extend(&chunk[..end]);
+            lines.push(self.take());
+            chunk = &chunk[end + 1..];
+        }
+        self.extend(chunk);
+
+        lines
+    }
+
+    /// The last line, if the body does not end with a newline.
+    fn finish(mut self) -> Option<Line> {
+        if self.buffer.is_empty() && !self.overlong {
+            return None;
+        }
+        Some(self.take())
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        if self.overlong {
+            return;
+        }
+        if self.buffer.len() + bytes.len() > self.max_len {
+            self.overlong = true;
+            self.buffer = Vec::new();
+            return;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn take(&mut self) -> Line {
+        self.number += 1;
+        if std::mem::take(&mut self.overlong) {
+            return Line::TooLong(self.number);
+        }
+        Line::Text(self.number, std::mem::take(&mut self.buffer))
+    }
+}
+
+/// A parsed and validated line waiting for its batch to be written.
+struct PendingPost {
+    line: usize,
+    author_id: Uuid,
+    post: ImportedPost,
+    request: CreatePostRequest,
+}
+
+struct Importer<'a> {
+    pool: &'a PgPool,
+    config: &'a Config,
+    user_id: Uuid,
+    is_admin: bool,
+    batch_size: usize,
+    /// Author ids by email, `None` for unknown emails.
+    authors: HashMap<String, Option<Uuid>>,
+    pending: Vec<PendingPost>,
+    summary: ImportSummary,
+}
+
+impl<'a> Importer<'a> {
+    fn new(pool: &'a PgPool, config: &'a Config, user_id: Uuid, is_admin: bool, batch_size: usize) -> Self {
+        Self {
+            pool,
+            config,
+            user_id,
+            is_admin,
+            batch_size,
+            authors: HashMap::new(),
+            pending: Vec::with_capacity(batch_size),
+            summary: ImportSummary::default(),
+        }
+    }
+
+    async fn add(&mut self, line: Line) -> Result<(), sqlx::Error> {
+        let (number, bytes) = match line {
+            Line::Text(number, bytes) => (number, bytes),
+            Line::TooLong(number) => {
+                self.summary.lines += 1;
+                self.fail(number, HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                    "error": format!("Line exceeds {} bytes", MAX_LINE_BYTES)
+                })))
+                .await;
+                return Ok(());
+            }
+        };
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        self.summary.lines += 1;
+
+        let post: ImportedPost = match serde_json::from_slice(&bytes) {
+            Ok(post) => post,
+            Err(e) => {
+                self.fail(number, HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Invalid JSON",
+                    "details": e.to_string()
+                })))
+                .await;
+                return Ok(());
+            }
+        };
+
+        let request = post.to_create_request();
+        if let Err(errors) = request.validate() {
+            self.fail(number, HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Validation failed",
+                "details": errors
+            })))
+            .await;
+            return Ok(());
+        }
+
+        let author_id = match post.author_email.as_deref() {
+            Some(email) if self.is_admin => match self.find_author(email).await? {
+                Some(id) => id,
+                None => {
+                    self.fail(number, HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                        "error": "Unknown author",
+                        "details": email
+                    })))
+                    .await;
+                    return Ok(());
+                }
+            },
+            _ => self.user_id,
+        };
+
+        self.pending.push(PendingPost {
+            line: number,
+            author_id,
+            post,
+            request,
+        });
+        if self.pending.len() >= self.batch_size {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the pending posts in one transaction, each in a savepoint so
+    /// a refused post leaves no trace.
+    async fn flush(&mut self) -> Result<(), sqlx::Error> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut refused = Vec::new();
+
+        for pending in &self.pending {
+            let mut savepoint = tx.begin().await?;
+            match insert_post(&mut savepoint, self.config, pending.author_id, &pending.request).await? {
+                Ok(post) => {
+                    let published_at = pending.post.publish_at.filter(|_| post.status == PostStatus::Published);
+                    restore_timestamps(&mut savepoint, post.id, pending.post.created_at, published_at).await?;
+                    savepoint.commit().await?;
+                }
+                Err(response) => {
+                    savepoint.rollback().await?;
+                    refused.push((pending.line, response));
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        self.summary.imported += self.pending.len() - refused.len();
+        self.pending.clear();
+        for (line, response) in refused {
+            self.fail(line, response).await;
+        }
+
+        Ok(())
+    }
+
+    /// Records a refused line with the status and error body `POST /posts`
+    /// would have answered with.
+    async fn fail(&mut self, line: usize, response: HttpResponse) {
+        self.summary.failed += 1;
+        if self.summary.errors.len() >= MAX_REPORTED_ERRORS {
+            self.summary.errors_truncated = true;
+            return;
+        }
+
+        let status = response.status().as_u16();
+        let error = to_bytes(response.into_body())
+            .await
+            .ok()
+            .and_then(|body| serde_json::from_slice(&body).ok());
+
+        self.summary.errors.push(ImportError { line, status, error });
+    }
+
+    async fn find_author(&mut self, email: &str) -> Result<Option<Uuid>, sqlx::Error> {
+        if let Some(id) = self.authors.get(email) {
+            return Ok(*id);
+        }
+        let id = TransferService::new(self.pool).find_user_by_email(email).await?;
+        self.authors.insert(email.to_string(), id);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(lines: Vec<Line>) -> Vec<(usize, Option<String>)> {
+        lines
+            .into_iter()
+            .map(|line| match line {
+                Line::Text(number, bytes) => (number, Some(String::from_utf8(bytes).unwrap())),
+                Line::TooLong(number) => (number, None),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lines_are_reassembled_across_chunks() {
+        let mut splitter = LineSplitter::new(100);
+
+        assert!(splitter.push(b"fir").is_empty());
+        assert_eq!(
+            describe(splitter.push(b"st\nsecond\nthi")),
+            vec![(1, Some("first".to_string())), (2, Some("second".to_string()))]
+        );
+        assert_eq!(describe(splitter.finish().into_iter().collect()), vec![(3, Some("thi".to_string()))]);
+    }
+
+    #[test]
+    fn a_trailing_newline_leaves_no_last_line() {
+        let mut splitter = LineSplitter::new(100);
+
+        assert_eq!(describe(splitter.push(b"only\n")), vec![(1, Some("only".to_string()))]);
+        assert!(splitter.finish().is_none());
+    }
+
+    #[test]
+    fn overlong_lines_are_reported_and_dropped() {
+        let mut splitter = LineSplitter::new(4);
+
+        assert!(splitter.push(b"abc").is_empty());
+        assert_eq!(
+            describe(splitter.push(b"defg\nok\n")),
+            vec![(1, None), (2, Some("ok".to_string()))]
+        );
+        assert!(splitter.push(b"toolong").is_empty());
+        assert_eq!(describe(splitter.finish().into_iter().collect()), vec![(3, None)]);
+    }
+}
\ No newline at end of file
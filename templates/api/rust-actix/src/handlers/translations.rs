@@ -0,0 +1,288 @@
+// `requested_locales` rejects a malformed `?lang=` with the finished 400 response;
+// handlers return it directly, so it stays unboxed.
+#![allow(clippy::result_large_err)]
+
+use std::cmp::Reverse;
+
+use actix_web::{
+    dev::HttpServiceFactory,
+    http::header::{AcceptLanguage, Preference, Quality},
+    web, HttpMessage, HttpRequest, HttpResponse, Result,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    database::Database,
+    middleware::{viewer_id, AuthMiddleware},
+    models::{
+        negotiate_locale, normalize_locale, LocaleQuery, PostTranslations, PostWithUser, TranslationRequest,
+    },
+    services::{delete_translation, render_content, save_translation, PostService, TranslationService},
+};
+
+use super::posts::{find_writable_post, PostWrite};
+
+/// Translation routes nested under `/posts`.
+pub fn post_scope() -> impl HttpServiceFactory {
+    web::scope("/{id}/translations")
+        .route("", web::get().to(get_translations))
+        .route("/{locale}", web::get().to(get_translation))
+        .service(
+            web::scope("")
+                .wrap(AuthMiddleware)
+                .route("/{locale}", web::put().to(put_translation))
+                .route("/{locale}", web::delete().to(delete_post_translation))
+        )
+}
+
+/// Locales the client asked for, most preferred first: `?lang=` followed
+/// by the `Accept-Language` ranges, with `*` for a wildcard. Malformed
+/// header ranges are skipped; a malformed `?lang=` is refused.
+pub(super) fn requested_locales(req: &HttpRequest, query: &LocaleQuery) -> Result<Vec<String>, HttpResponse> {
+    let mut locales = Vec::new();
+
+    if let Some(lang) = &query.lang {
+        match normalize_locale(lang) {
+            Some(locale) => locales.push(locale),
+            None => {
+                return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Invalid lang, expected a language tag such as en or pt-BR"
+                })));
+            }
+        }
+    }
+
+    if let Some(AcceptLanguage(mut ranges)) = req.get_header::<AcceptLanguage>() {
+        // Stable, so that ranges of equal quality keep their order
+        ranges.sort_by_key(|range| Reverse(range.quality));
+        locales.extend(
+            ranges
+                .into_iter()
+                .filter(|range| range.quality > Quality::ZERO)
+                .filter_map(|range| match range.item {
+                    Preference::Any => Some("*".to_string()),
+                    Preference::Specific(tag) => normalize_locale(tag.as_str()),
+                }),
+        );
+    }
+
+    Ok(locales)
+}
+
+/// The translations to serve `posts` in for `requested`; posts read in
+/// their canonical locale have none.
+pub(super) async fn negotiated_translations(
+    pool: &PgPool,
+    requested: &[String],
+    posts: &[PostWithUser],
+) -> Result<PostTranslations, sqlx::Error> {
+    let wanted: Vec<(Uuid, String)> = posts
+        .iter()
+        .filter_map(|post| {
+            let locale = negotiate_locale(requested, &post.locale, &post.translations);
+            (locale != post.locale).then(|| (post.id, locale.to_string()))
+        })
+        .collect();
+
+    TranslationService::new(pool).find_many(&wanted).await
+}
+
+pub async fn get_translations(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    let pool = db.get_pool();
+
+    if let Err(response) = find_visible_post(&req, pool, post_id).await {
+        return Ok(response);
+    }
+
+    match TranslationService::new(pool).list(post_id).await {
+        Ok(translations) => Ok(HttpResponse::Ok().json(translations)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch translations"
+            })))
+        }
+    }
+}
+
+pub async fn get_translation(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let (post_id, locale) = path.into_inner();
+    let pool = db.get_pool();
+
+    let Some(locale) = normalize_locale(&locale) else {
+        return Ok(translation_not_found());
+    };
+    if let Err(response) = find_visible_post(&req, pool, post_id).await {
+        return Ok(response);
+    }
+
+    match TranslationService::new(pool).find(post_id, &locale).await {
+        Ok(Some(translation)) => Ok(HttpResponse::Ok().json(translation)),
+        Ok(None) => Ok(translation_not_found()),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+/// Adds a translation of a post the user owns or edits, or replaces the
+/// existing one for the locale.
+pub async fn put_translation(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    body: web::Json<TranslationRequest>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    if let Err(errors) = body.validate() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Validation failed",
+            "details": errors
+        })));
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let (post_id, locale) = path.into_inner();
+    let Some(locale) = normalize_locale(&locale) else {
+        return Ok(invalid_locale());
+    };
+    let pool = db.get_pool();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        let post = match find_writable_post(&mut tx, user_id, post_id, PostWrite::Update).await? {
+            Ok(post) => post,
+            Err(response) => return Ok(Err(response)),
+        };
+        if post.locale == locale {
+            return Ok(Err(canonical_locale(&post.locale)));
+        }
+
+        let rendered = render_content(post.content_format, &body.content);
+        let saved = save_translation(&mut tx, post_id, &locale, &body.title, &body.content, &rendered).await?;
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(Ok(saved))
+    }
+    .await;
+
+    match result {
+        Ok(Ok((translation, true))) => Ok(HttpResponse::Created().json(translation)),
+        Ok(Ok((translation, false))) => Ok(HttpResponse::Ok().json(translation)),
+        Ok(Err(response)) => Ok(response),
+        Err(e) => {
+            log::error!("Failed to save translation: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to save translation"
+            })))
+        }
+    }
+}
+
+pub async fn delete_post_translation(
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let (post_id, locale) = path.into_inner();
+    let Some(locale) = normalize_locale(&locale) else {
+        return Ok(translation_not_found());
+    };
+    let pool = db.get_pool();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        let post = match find_writable_post(&mut tx, user_id, post_id, PostWrite::Update).await? {
+            Ok(post) => post,
+            Err(response) => return Ok(Err(response)),
+        };
+        if post.locale == locale {
+            return Ok(Err(canonical_locale(&post.locale)));
+        }
+
+        let deleted = delete_translation(&mut tx, post_id, &locale).await?;
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(Ok(deleted))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(true)) => Ok(HttpResponse::NoContent().finish()),
+        Ok(Ok(false)) => Ok(translation_not_found()),
+        Ok(Err(response)) => Ok(response),
+        Err(e) => {
+            log::error!("Failed to delete translation: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete translation"
+            })))
+        }
+    }
+}
+
+async fn find_visible_post(req: &HttpRequest, pool: &PgPool, post_id: Uuid) -> Result<PostWithUser, HttpResponse> {
+    let viewer = viewer_id(req).await;
+
+    match PostService::new(pool).find_with_user(post_id).await {
+        Ok(Some(post)) if post.is_visible_to(viewer) => Ok(post),
+        Ok(_) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Post not found"
+        }))),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+fn canonical_locale(locale: &str) -> HttpResponse {
+    HttpResponse::Conflict().json(serde_json::json!({
+        "error": format!("The post is written in {}; update the post itself instead", locale)
+    }))
+}
+
+fn invalid_locale() -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid locale, expected a language tag such as en or pt-BR"
+    }))
+}
+
+fn translation_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Translation not found"
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,420 @@
+// tus checks fail with the complete protocol response (412 with `Tus-Version`),
+// which handlers send as is; it is not worth boxing on that path.
+#![allow(clippy::result_large_err)]
+
+use std::path::Path;
+
+use actix_web::{
+    dev::HttpServiceFactory,
+    http::{
+        header::{HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+        Method, StatusCode,
+    },
+    web, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder, Result,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{parse_upload_metadata, ResumableUpload},
+    services::{NewResumableUpload, ResumableUploadService, UploadLocks},
+    storage::StorageBackend,
+};
+
+use super::attachments::{clean_filename, find_own_post, store_attachment, UploadedFile};
+
+/// The tus protocol version spoken here.
+pub const TUS_VERSION: &str = "1.0.0";
+const TUS_EXTENSIONS: &str = "creation,expiration,termination";
+const CHUNK_CONTENT_TYPE: &str = "application/offset+octet-stream";
+
+/// tus 1.0 endpoint for resumable uploads that become post attachments.
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/uploads")
+        .route("", web::method(Method::OPTIONS).to(tus_options))
+        .route("/{id}", web::method(Method::OPTIONS).to(tus_options))
+        .service(
+            web::scope("")
+                .wrap(AuthMiddleware)
+                .route("", web::post().to(create_upload))
+                .route("/{id}", web::head().to(get_upload_offset))
+                .route("/{id}", web::patch().to(upload_chunk))
+                .route("/{id}", web::delete().to(terminate_upload))
+        )
+}
+
+/// Describes the server's tus support.
+pub async fn tus_options(config: web::Data<Config>) -> HttpResponse {
+    tus_response(StatusCode::NO_CONTENT)
+        .insert_header(("Tus-Version", TUS_VERSION))
+        .insert_header(("Tus-Extension", TUS_EXTENSIONS))
+        .insert_header(("Tus-Max-Size", config.upload_max_bytes.to_string()))
+        .finish()
+}
+
+/// Creation extension: registers an upload of `Upload-Length` bytes. The
+/// `Upload-Metadata` header carries `post_id` and optionally `filename`,
+/// `filetype` and `private`.
+pub async fn create_upload(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(response) = check_tus_version(&req) {
+        return Ok(response);
+    }
+
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => return Ok(tus_error(StatusCode::UNAUTHORIZED, "Unauthorized")),
+    };
+
+    if req.headers().contains_key("Upload-Defer-Length") {
+        return Ok(tus_error(StatusCode::BAD_REQUEST, "Deferred upload length is not supported"));
+    }
+
+    let length = match header_str(&req, "Upload-Length").and_then(|value| value.parse::<i64>().ok()) {
+        Some(length) if length > 0 => length,
+        _ => {
+            return Ok(tus_error(
+                StatusCode::BAD_REQUEST,
+                "Upload-Length must be a positive number of bytes",
+            ));
+        }
+    };
+    if length > config.upload_max_bytes as i64 {
+        return Ok(tus_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!("File exceeds the {} byte upload limit", config.upload_max_bytes),
+        ));
+    }
+
+    let raw_metadata = header_str(&req, "Upload-Metadata").unwrap_or_default();
+    let mut metadata = match parse_upload_metadata(raw_metadata) {
+        Ok(metadata) => metadata,
+        Err(message) => return Ok(tus_error(StatusCode::BAD_REQUEST, &message)),
+    };
+
+    let Some(post_id) = metadata.get("post_id").and_then(|id| Uuid::parse_str(id).ok()) else {
+        return Ok(tus_error(
+            StatusCode::BAD_REQUEST,
+            "Upload-Metadata must include the post_id to attach the file to",
+        ));
+    };
+    let private = match metadata.get("private").map(String::as_str) {
+        None | Some("false") => false,
+        Some("" | "true") => true,
+        Some(_) => return Ok(tus_error(StatusCode::BAD_REQUEST, "Metadata 'private' must be true or false")),
+    };
+    let filename = metadata
+        .remove("filename")
+        .map(|filename| clean_filename(&filename))
+        .filter(|filename| !filename.is_empty())
+        .unwrap_or_else(|| "file".to_string());
+
+    let pool = db.get_pool();
+
+    if let Err(response) = find_own_post(pool, post_id, user_id).await {
+        return Ok(with_tus_header(response));
+    }
+
+    let upload = ResumableUploadService::new(pool, Path::new(&config.resumable_upload_path))
+        .create(
+            user_id,
+            NewResumableUpload {
+                post_id,
+                filename,
+                declared_type: metadata.remove("filetype").filter(|filetype| !filetype.is_empty()),
+                private,
+                metadata: raw_metadata.to_string(),
+                length,
+            },
+            config.resumable_upload_expiration_secs,
+        )
+        .await;
+
+    match upload {
+        Ok(upload) => Ok(tus_response(StatusCode::CREATED)
+            .insert_header((LOCATION, format!("{}/{}", req.path(), upload.id)))
+            .insert_header(("Upload-Expires", http_date(upload.expires_at)))
+            .finish()),
+        Err(e) => {
+            log::error!("Failed to create upload: {:?}", e);
+            Ok(tus_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create upload"))
+        }
+    }
+}
+
+/// Reports how many bytes of an upload have been received.
+pub async fn get_upload_offset(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Err(response) = check_tus_version(&req) {
+        return Ok(response);
+    }
+
+    let upload = match find_upload(&req, path.into_inner(), &db, &config).await {
+        Ok(upload) => upload,
+        Err(response) => return Ok(response),
+    };
+
+    let mut response = tus_response(StatusCode::OK);
+    response
+        .insert_header(("Upload-Offset", upload.upload_offset.to_string()))
+        .insert_header(("Upload-Length", upload.upload_length.to_string()))
+        .insert_header(("Upload-Expires", http_date(upload.expires_at)))
+        .insert_header((CACHE_CONTROL, "no-store"));
+    if !upload.metadata.is_empty() {
+        response.insert_header(("Upload-Metadata", upload.metadata.as_str()));
+    }
+    if let Some(attachment_id) = upload.attachment_id {
+        response.insert_header(("Attachment-Id", attachment_id.to_string()));
+    }
+
+    Ok(response.finish())
+}
+
+/// Appends the request body to an upload at `Upload-Offset`. Once all bytes
+/// are in, the file is stored as an attachment of the upload's post.
+pub async fn upload_chunk(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Payload,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    storage: web::Data<dyn StorageBackend>,
+    locks: web::Data<UploadLocks>,
+) -> Result<HttpResponse> {
+    if let Err(response) = check_tus_version(&req) {
+        return Ok(response);
+    }
+
+    if header_str(&req, CONTENT_TYPE.as_str()) != Some(CHUNK_CONTENT_TYPE) {
+        return Ok(tus_error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            &format!("Content-Type must be {}", CHUNK_CONTENT_TYPE),
+        ));
+    }
+
+    let Some(offset) = header_str(&req, "Upload-Offset").and_then(|value| value.parse::<i64>().ok()) else {
+        return Ok(tus_error(StatusCode::BAD_REQUEST, "Upload-Offset header is required"));
+    };
+
+    let upload_id = path.into_inner();
+    let Some(_lock) = locks.try_lock(upload_id) else {
+        return Ok(tus_error(StatusCode::LOCKED, "Upload is already receiving data"));
+    };
+
+    let upload = match find_upload(&req, upload_id, &db, &config).await {
+        Ok(upload) => upload,
+        Err(response) => return Ok(response),
+    };
+
+    let remaining = upload.upload_length - upload.upload_offset;
+    if header_str(&req, CONTENT_LENGTH.as_str())
+        .and_then(|value| value.parse::<i64>().ok())
+        .is_some_and(|length| length > remaining)
+    {
+        return Ok(chunk_too_large());
+    }
+
+    if offset != upload.upload_offset {
+        return Ok(tus_response(StatusCode::CONFLICT)
+            .insert_header(("Upload-Offset", upload.upload_offset.to_string()))
+            .json(serde_json::json!({
+                "error": "Upload-Offset does not match the upload's current offset"
+            })));
+    }
+
+    if upload.completed_at.is_some() {
+        return Ok(upload_progress(upload.upload_offset, upload.expires_at, upload.attachment_id));
+    }
+
+    let pool = db.get_pool();
+    let service = ResumableUploadService::new(pool, Path::new(&config.resumable_upload_path));
+
+    let outcome = match service
+        .write_chunk(&upload, body, config.resumable_upload_expiration_secs)
+        .await
+    {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            log::error!("Failed to write upload chunk: {:?}", e);
+            return Ok(tus_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to write upload"));
+        }
+    };
+
+    if outcome.interrupted {
+        return Ok(tus_response(StatusCode::BAD_REQUEST)
+            .insert_header(("Upload-Offset", outcome.offset.to_string()))
+            .json(serde_json::json!({
+                "error": "Request body was interrupted; resume from Upload-Offset"
+            })));
+    }
+    if outcome.overflowed {
+        return Ok(chunk_too_large());
+    }
+    if outcome.offset < upload.upload_length {
+        return Ok(upload_progress(outcome.offset, outcome.expires_at, None));
+    }
+
+    // Every byte is in: hand the file over to attachment storage
+    let completed = async {
+        let post = find_own_post(pool, upload.post_id, upload.user_id).await?;
+        let data = service.read_file(&upload).await.map_err(|e| {
+            log::error!("Failed to read completed upload: {:?}", e);
+            tus_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read upload")
+        })?;
+        let file = UploadedFile {
+            filename: upload.filename.clone(),
+            declared_type: upload.declared_type.clone(),
+            data,
+            private: upload.private,
+        };
+        store_attachment(pool, storage.get_ref(), &config, post.id, upload.user_id, file).await
+    }
+    .await;
+
+    match completed {
+        Ok(attachment) => {
+            if let Err(e) = service.complete(upload.id, attachment.id).await {
+                log::error!("Failed to mark upload {} complete: {:?}", upload.id, e);
+            }
+            Ok(upload_progress(outcome.offset, outcome.expires_at, Some(attachment.id)))
+        }
+        Err(response) => {
+            // Rejected files can't become attachments, so the upload is done
+            // for; after server errors a PATCH at the final offset retries
+            if response.status().is_client_error() {
+                if let Err(e) = service.delete(upload.id).await {
+                    log::error!("Failed to delete rejected upload {}: {:?}", upload.id, e);
+                }
+            }
+            Ok(with_tus_header(response))
+        }
+    }
+}
+
+/// Termination extension: abandons an upload and its received data.
+pub async fn terminate_upload(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    locks: web::Data<UploadLocks>,
+) -> Result<HttpResponse> {
+    if let Err(response) = check_tus_version(&req) {
+        return Ok(response);
+    }
+
+    let upload_id = path.into_inner();
+    let Some(_lock) = locks.try_lock(upload_id) else {
+        return Ok(tus_error(StatusCode::LOCKED, "Upload is already receiving data"));
+    };
+
+    let upload = match find_upload(&req, upload_id, &db, &config).await {
+        Ok(upload) => upload,
+        Err(response) => return Ok(response),
+    };
+
+    match ResumableUploadService::new(db.get_pool(), Path::new(&config.resumable_upload_path))
+        .delete(upload.id)
+        .await
+    {
+        Ok(()) => Ok(tus_response(StatusCode::NO_CONTENT).finish()),
+        Err(e) => {
+            log::error!("Failed to terminate upload: {:?}", e);
+            Ok(tus_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to terminate upload"))
+        }
+    }
+}
+
+/// Fetches one of the caller's uploads.
+async fn find_upload(
+    req: &HttpRequest,
+    upload_id: Uuid,
+    db: &Database,
+    config: &Config,
+) -> Result<ResumableUpload, HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => return Err(tus_error(StatusCode::UNAUTHORIZED, "Unauthorized")),
+    };
+
+    match ResumableUploadService::new(db.get_pool(), Path::new(&config.resumable_upload_path))
+        .find(upload_id, user_id)
+        .await
+    {
+        Ok(Some(upload)) if upload.is_expired() => Err(tus_error(StatusCode::GONE, "Upload has expired")),
+        Ok(Some(upload)) => Ok(upload),
+        Ok(None) => Err(tus_error(StatusCode::NOT_FOUND, "Upload not found")),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Err(tus_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))
+        }
+    }
+}
+
+fn check_tus_version(req: &HttpRequest) -> Result<(), HttpResponse> {
+    if header_str(req, "Tus-Resumable") == Some(TUS_VERSION) {
+        return Ok(());
+    }
+
+    Err(tus_response(StatusCode::PRECONDITION_FAILED)
+        .insert_header(("Tus-Version", TUS_VERSION))
+        .json(serde_json::json!({
+            "error": format!("Tus-Resumable: {} is required", TUS_VERSION)
+        })))
+}
+
+fn header_str<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers().get(name)?.to_str().ok()
+}
+
+/// Every tus response carries the protocol version.
+fn tus_response(status: StatusCode) -> HttpResponseBuilder {
+    let mut response = HttpResponse::build(status);
+    response.insert_header(("Tus-Resumable", TUS_VERSION));
+    response
+}
+
+fn with_tus_header(mut response: HttpResponse) -> HttpResponse {
+    response.headers_mut().insert(
+        HeaderName::from_static("tus-resumable"),
+        HeaderValue::from_static(TUS_VERSION),
+    );
+    response
+}
+
+fn tus_error(status: StatusCode, message: &str) -> HttpResponse {
+    tus_response(status).json(serde_json::json!({
+        "error": message
+    }))
+}
+
+fn chunk_too_large() -> HttpResponse {
+    tus_error(StatusCode::PAYLOAD_TOO_LARGE, "Request body exceeds the rest of Upload-Length")
+}
+
+/// Successful PATCH response; `attachment_id` is set once the upload is complete.
+fn upload_progress(offset: i64, expires_at: DateTime<Utc>, attachment_id: Option<Uuid>) -> HttpResponse {
+    let mut response = tus_response(StatusCode::NO_CONTENT);
+    response
+        .insert_header(("Upload-Offset", offset.to_string()))
+        .insert_header(("Upload-Expires", http_date(expires_at)));
+    if let Some(attachment_id) = attachment_id {
+        response.insert_header(("Attachment-Id", attachment_id.to_string()));
+    }
+    response.finish()
+}
+
+/// RFC 9110 HTTP-date, as tus uses for `Upload-Expires`.
+fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
\ No newline at end of file
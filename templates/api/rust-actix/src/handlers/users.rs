@@ -0,0 +1,118 @@
+use actix_web::{dev::HttpServiceFactory, web, HttpMessage, HttpRequest, HttpResponse, Result};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    database::Database,
+    middleware::AuthMiddleware,
+    models::{User, UserDocument, UserResponse},
+};
+
+use super::{auth::get_current_user, bookmarks, patch::apply_patch};
+
+pub fn config() -> impl HttpServiceFactory {
+    web::scope("/users")
+        .wrap(AuthMiddleware)
+        .route("/me", web::get().to(get_current_user))
+        .route("/me", web::patch().to(patch_current_user))
+        .route("/me/bookmarks", web::get().to(bookmarks::get_bookmarks))
+        .service(bookmarks::folder_scope())
+}
+
+/// Applies a JSON Merge Patch or JSON Patch to the current user's
+/// editable fields (see [`UserDocument`]).
+pub async fn patch_current_user(
+    req: HttpRequest,
+    body: web::Bytes,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Unauthorized"
+            })));
+        }
+    };
+
+    let pool = db.get_pool();
+
+    let user = async {
+        let mut tx = pool.begin().await?;
+
+        let user = sqlx::query_as!(
+            User,
+            "SELECT * FROM users WHERE id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(user) = user else {
+            return Ok(Err(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "User not found"
+            }))));
+        };
+        let original = UserDocument::from(&user);
+
+        let document = match apply_patch(&req, &body, &original) {
+            Ok(document) => document,
+            Err(response) => return Ok(Err(response)),
+        };
+        if let Err(errors) = document.validate() {
+            return Ok(Err(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Validation failed",
+                "details": errors
+            }))));
+        }
+        if document == original {
+            return Ok(Ok(user));
+        }
+
+        if document.email != original.email {
+            let taken = sqlx::query_scalar!(
+                r#"SELECT EXISTS(SELECT 1 FROM users WHERE email = $1) as "exists!""#,
+                document.email
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if taken {
+                return Ok(Err(HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "Email already exists"
+                }))));
+            }
+        }
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET email = $2, first_name = $3, last_name = $4, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+            user_id,
+            document.email,
+            document.first_name,
+            document.last_name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(Ok(user))
+    }
+    .await;
+
+    match user {
+        Ok(Ok(user)) => Ok(HttpResponse::Ok().json(UserResponse::from(user))),
+        Ok(Err(response)) => Ok(response),
+        Err(e) => {
+            log::error!("Failed to update user: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update user"
+            })))
+        }
+    }
+}
\ No newline at end of file
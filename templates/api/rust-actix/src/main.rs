@@ -1,7 +1,9 @@
 use actix_cors::Cors;
-use actix_web::{web, App, HttpServer, Result};
+use actix_web::{web, App, HttpResponse, HttpServer, Result};
 use env_logger::Env;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
 mod config;
 mod database;
@@ -9,6 +11,7 @@ mod handlers;
 mod middleware;
 mod models;
 mod services;
+mod storage;
 
 use config::Config;
 use database::Database;
@@ -20,25 +23,91 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config = Config::from_env();
     let database = Database::new(&config.database_url).await?;
+    if !services::PostService::new(&database.pool)
+        .search_vector_uses(&config.search_language)
+        .await?
+    {
+        return Err(format!(
+            "SEARCH_LANGUAGE '{}' does not match the configuration of posts.search_vector",
+            config.search_language
+        )
+        .into());
+    }
+    let storage = storage::from_config(&config).await?;
+    std::fs::create_dir_all(&config.resumable_upload_path)?;
+    let upload_locks = web::Data::new(services::UploadLocks::default());
+    let views = Arc::new(services::ViewCounter::new(Duration::from_secs(
+        config.view_dedup_window_secs,
+    )));
+
+    services::spawn_scheduled_publisher(
+        database.pool.clone(),
+        Duration::from_secs(config.publish_interval_secs),
+    );
+    services::spawn_trash_purger(
+        database.pool.clone(),
+        Duration::from_secs(config.trash_purge_interval_secs),
+        config.trash_retention_days,
+    );
+    services::spawn_blob_sweeper(
+        database.pool.clone(),
+        storage.clone(),
+        Duration::from_secs(config.blob_sweep_interval_secs),
+    );
+    services::spawn_upload_expirer(
+        database.pool.clone(),
+        config.resumable_upload_path.clone().into(),
+        Duration::from_secs(config.resumable_upload_sweep_interval_secs),
+    );
+
+    services::spawn_view_flusher(
+        database.pool.clone(),
+        views.clone(),
+        Duration::from_secs(config.view_flush_interval_secs),
+    );
 
     log::info!("Starting server at {}:{}", config.server_host, config.server_port);
 
+    let pool = database.pool.clone();
+    let server_views = views.clone();
     HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
             .allow_any_header()
+            .expose_any_header()
             .max_age(3600);
 
         App::new()
             .app_data(web::Data::new(database.clone()))
             .app_data(web::Data::new(config.clone()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(upload_locks.clone())
+            .app_data(web::Data::from(server_views.clone()))
+            .app_data(web::QueryConfig::default().error_handler(|err, _req| {
+                let details = err.to_string();
+                actix_web::error::InternalError::from_response(
+                    err,
+                    HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": "Invalid query parameters",
+                        "details": details
+                    })),
+                )
+                .into()
+            }))
             .wrap(cors)
             .wrap(actix_web::middleware::Logger::default())
             .service(
                 web::scope("/api/v1")
                     .service(handlers::auth::config())
+                    .service(handlers::users::config())
                     .service(handlers::posts::config())
+                    .service(handlers::comments::config())
+                    .service(handlers::tags::config())
+                    .service(handlers::attachments::config())
+                    .service(handlers::uploads::config())
+                    .service(handlers::feeds::config())
+                    .service(handlers::moderation::config())
             )
             .service(handlers::health::health_check)
     })
@@ -46,5 +115,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .run()
     .await?;
 
+    // Keep the views counted since the last flush
+    if let Err(e) = views.flush(&pool).await {
+        log::error!("Failed to flush post views: {:?}", e);
+    }
+
     Ok(())
 }
\ No newline at end of file
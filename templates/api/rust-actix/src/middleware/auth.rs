@@ -1,6 +1,6 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    web, Error, HttpMessage, HttpResponse,
+    web, Error, HttpMessage, HttpRequest, HttpResponse,
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
 use std::{
@@ -9,10 +9,44 @@ use std::{
 };
 use uuid::Uuid;
 
-use crate::{config::Config, services::AuthService};
+use crate::{config::Config, database::Database, services::AuthService};
 
 pub struct AuthMiddleware;
 
+/// Resolves the signed-in user on public routes that tailor their output to
+/// the viewer. Missing or invalid tokens and suspended accounts yield an
+/// anonymous viewer.
+pub async fn viewer_id(req: &HttpRequest) -> Option<Uuid> {
+    let token = req
+        .headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    let config = req.app_data::<web::Data<Config>>()?;
+    let claims = AuthService::new(config.jwt_secret.clone())
+        .validate_token(token)
+        .ok()?;
+    let user_id = Uuid::parse_str(&claims.sub).ok()?;
+
+    // Same check as AuthMiddleware: tokens outlive a suspension
+    let db = req.app_data::<web::Data<Database>>()?;
+    let active = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE id = $1 AND suspended_at IS NULL",
+        user_id
+    )
+    .fetch_optional(db.get_pool())
+    .await;
+
+    match active {
+        Ok(active) => active,
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            None
+        }
+    }
+}
+
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -91,6 +125,42 @@ where
                 Ok(claims) => {
                     match Uuid::parse_str(&claims.sub) {
                         Ok(user_id) => {
+                            // Tokens stay valid until they expire, so check
+                            // for suspension on every request
+                            let db = req.app_data::<web::Data<Database>>().unwrap();
+                            let suspended = sqlx::query_scalar!(
+                                r#"SELECT suspended_at IS NOT NULL as "suspended!" FROM users WHERE id = $1"#,
+                                user_id
+                            )
+                            .fetch_optional(db.get_pool())
+                            .await;
+
+                            match suspended {
+                                Ok(Some(false)) => {}
+                                Ok(Some(true)) => {
+                                    return Ok(req.into_response(
+                                        HttpResponse::Forbidden().json(serde_json::json!({
+                                            "error": "Account suspended"
+                                        }))
+                                    ));
+                                }
+                                Ok(None) => {
+                                    return Ok(req.into_response(
+                                        HttpResponse::Unauthorized().json(serde_json::json!({
+                                            "error": "User not found"
+                                        }))
+                                    ));
+                                }
+                                Err(e) => {
+                                    log::error!("Database error: {:?}", e);
+                                    return Ok(req.into_response(
+                                        HttpResponse::InternalServerError().json(serde_json::json!({
+                                            "error": "Database error"
+                                        }))
+                                    ));
+                                }
+                            }
+
                             req.extensions_mut().insert(user_id);
                             srv.call(req).await
                         }
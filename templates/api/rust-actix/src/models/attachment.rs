@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An attachment together with the stored blob it points to.
+#[derive(Debug, Clone, FromRow)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub sha256: String,
+    pub filename: String,
+    pub private: bool,
+    pub created_at: DateTime<Utc>,
+    pub storage_key: String,
+    pub thumbnail_key: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttachmentResponse {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub private: bool,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    /// When the URLs stop working, if they are signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which file of an attachment to download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentVariant {
+    Original,
+    Thumbnail,
+}
+
+impl AttachmentVariant {
+    /// Path segment of the download route, e.g. `/attachments/{id}/content`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttachmentVariant::Original => "content",
+            AttachmentVariant::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+/// Signature of a private attachment's download URL.
+#[derive(Debug, Deserialize)]
+pub struct DownloadQuery {
+    pub expires: Option<i64>,
+    pub signature: Option<String>,
+}
\ No newline at end of file
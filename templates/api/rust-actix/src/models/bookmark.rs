@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::PostResponse;
+
+/// Posts the signed-in viewer bookmarked, out of those being returned.
+pub type ViewerBookmarks = HashSet<Uuid>;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Bookmark {
+    pub post_id: Uuid,
+    /// Folder the bookmark is filed in; `None` for unfiled bookmarks.
+    pub folder_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A private folder of bookmarks.
+#[derive(Debug, FromRow, Serialize)]
+pub struct BookmarkFolder {
+    pub id: Uuid,
+    pub name: String,
+    pub bookmark_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A bookmark together with the post it saves.
+#[derive(Debug, Serialize)]
+pub struct BookmarkedPost {
+    pub folder_id: Option<Uuid>,
+    pub bookmarked_at: DateTime<Utc>,
+    pub post: PostResponse,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BookmarkRequest {
+    /// Folder to file the bookmark in; unfiled when absent.
+    pub folder_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BookmarkFolderRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BookmarkListQuery {
+    /// Only list the bookmarks in this folder.
+    pub folder_id: Option<Uuid>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
\ No newline at end of file
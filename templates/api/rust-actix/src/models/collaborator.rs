@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// What a collaborator may do with a shared post.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "collaborator_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CollaboratorRole {
+    /// Can read and update the post, even while it is unpublished.
+    Editor,
+    /// Can read the post while it is unpublished.
+    Viewer,
+}
+
+/// A user a post is shared with.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Collaborator {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: CollaboratorRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AddCollaboratorRequest {
+    /// Email of the user to share the post with.
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+    pub role: CollaboratorRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharedPostsQuery {
+    pub role: Option<CollaboratorRole>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
\ No newline at end of file
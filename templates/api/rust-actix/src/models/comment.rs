@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub depth: i32,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, max = 10000, message = "Comment must be between 1 and 10000 characters"))]
+    pub content: String,
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCommentRequest {
+    #[validate(length(min = 1, max = 10000, message = "Comment must be between 1 and 10000 characters"))]
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// Deepest reply level to include; top-level comments are depth 0.
+    pub depth: Option<i32>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct CommentWithUser {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub depth: i32,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub reply_count: i64,
+    pub user_first_name: String,
+    pub user_last_name: String,
+}
+
+/// The public face of a comment's author; comment threads are readable
+/// without signing in, so no email.
+#[derive(Debug, Serialize)]
+pub struct CommentAuthor {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+/// A comment with its replies. Deleted comments that still have replies
+/// are kept as placeholders without content or author.
+#[derive(Debug, Serialize)]
+pub struct CommentResponse {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub depth: i32,
+    pub deleted: bool,
+    pub content: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub user: Option<CommentAuthor>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Live direct replies, including any beyond the requested depth.
+    pub reply_count: i64,
+    pub replies: Vec<CommentResponse>,
+}
+
+impl From<Comment> for CommentResponse {
+    fn from(comment: Comment) -> Self {
+        Self {
+            id: comment.id,
+            post_id: comment.post_id,
+            parent_id: comment.parent_id,
+            depth: comment.depth,
+            deleted: false,
+            content: Some(comment.content),
+            user_id: Some(comment.user_id),
+            user: None,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            reply_count: 0,
+            replies: Vec::new(),
+        }
+    }
+}
+
+impl From<CommentWithUser> for CommentResponse {
+    fn from(comment: CommentWithUser) -> Self {
+        if comment.deleted_at.is_some() {
+            return Self {
+                id: comment.id,
+                post_id: comment.post_id,
+                parent_id: comment.parent_id,
+                depth: comment.depth,
+                deleted: true,
+                content: None,
+                user_id: None,
+                user: None,
+                created_at: comment.created_at,
+                updated_at: comment.updated_at,
+                reply_count: comment.reply_count,
+                replies: Vec::new(),
+            };
+        }
+
+        Self {
+            id: comment.id,
+            post_id: comment.post_id,
+            parent_id: comment.parent_id,
+            depth: comment.depth,
+            deleted: false,
+            content: Some(comment.content),
+            user_id: Some(comment.user_id),
+            user: Some(CommentAuthor {
+                id: comment.user_id,
+                first_name: comment.user_first_name,
+                last_name: comment.user_last_name,
+            }),
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            reply_count: comment.reply_count,
+            replies: Vec::new(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Syndication formats, chosen by the feed URL's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "rss" => Some(FeedFormat::Rss),
+            "atom" => Some(FeedFormat::Atom),
+            "json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FeedQuery {
+    /// Comma-separated list of tags, as in `GET /posts`.
+    #[validate(length(min = 1, max = 255, message = "Tag filter must be between 1 and 255 characters"))]
+    pub tag: Option<String>,
+}
+
+/// A JSON Feed 1.1 document (https://www.jsonfeed.org/version/1.1/).
+#[derive(Debug, Serialize)]
+pub struct JsonFeed {
+    pub version: &'static str,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub content_html: String,
+    pub summary: String,
+    pub date_published: DateTime<Utc>,
+    pub date_modified: DateTime<Utc>,
+    pub authors: Vec<JsonFeedAuthor>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String,
+}
\ No newline at end of file
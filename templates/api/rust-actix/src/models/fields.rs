@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `?fields=` and `?expand=`, extracted by read endpoints next to their
+/// own query parameters.
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    /// Comma-separated top-level fields to return; every field when absent.
+    pub fields: Option<String>,
+    /// Comma-separated related resources to embed.
+    pub expand: Option<String>,
+}
+
+/// What a resource offers to sparse fieldsets and expansions.
+pub trait SparseFields {
+    /// Top-level response fields. `id` is always returned.
+    const FIELDS: &'static [&'static str];
+    /// Related resources `?expand=` can embed, under their own name.
+    const EXPANSIONS: &'static [&'static str];
+    /// Expansions included when a request names neither `fields` nor
+    /// `expand`, so existing clients keep getting them.
+    const DEFAULT_EXPAND: &'static [&'static str];
+}
+
+/// The fields and expansions a request asked for, checked against the
+/// resource's [`SparseFields`]. Services use it to decide which columns and
+/// joins a query needs; handlers use [`FieldSelection::project`] to trim the
+/// response to match.
+#[derive(Debug, Clone)]
+pub struct FieldSelection {
+    known_fields: &'static [&'static str],
+    known_expansions: &'static [&'static str],
+    /// `None` selects every field.
+    fields: Option<BTreeSet<&'static str>>,
+    expand: BTreeSet<&'static str>,
+}
+
+impl FieldSelection {
+    /// Parses a request's selection; unknown names are an error message
+    /// for a `400` response.
+    pub fn parse<T: SparseFields>(query: &FieldsQuery) -> Result<Self, String> {
+        let fields = query
+            .fields
+            .as_deref()
+            .map(|fields| resolve_names(fields, T::FIELDS, "field"))
+            .transpose()?;
+
+        let expand = match query.expand.as_deref() {
+            Some(expand) => resolve_names(expand, T::EXPANSIONS, "expansion")?,
+            None if fields.is_none() => T::DEFAULT_EXPAND.iter().copied().collect(),
+            None => BTreeSet::new(),
+        };
+
+        Ok(Self {
+            known_fields: T::FIELDS,
+            known_expansions: T::EXPANSIONS,
+            fields,
+            expand,
+        })
+    }
+
+    pub fn includes(&self, field: &str) -> bool {
+        field == "id" || self.fields.as_ref().is_none_or(|fields| fields.contains(field))
+    }
+
+    pub fn expands(&self, name: &str) -> bool {
+        self.expand.contains(name)
+    }
+
+    /// Serializes `value` without the fields and expansions the selection
+    /// leaves out. Keys the resource does not declare, such as a search
+    /// result's `rank`, are kept.
+    pub fn project<S: Serialize>(&self, value: &S) -> Value {
+        let mut value = serde_json::to_value(value).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.retain(|key, _| {
+                if self.known_fields.contains(&key.as_str()) {
+                    self.includes(key)
+                } else if self.known_expansions.contains(&key.as_str()) {
+                    self.expands(key)
+                } else {
+                    true
+                }
+            });
+        }
+        value
+    }
+}
+
+fn resolve_names(
+    list: &str,
+    known: &'static [&'static str],
+    kind: &str,
+) -> Result<BTreeSet<&'static str>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            known
+                .iter()
+                .find(|known| **known == name)
+                .copied()
+                .ok_or_else(|| format!("Unknown {} '{}'", kind, name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Article;
+
+    impl SparseFields for Article {
+        const FIELDS: &'static [&'static str] = &["id", "title", "body"];
+        const EXPANSIONS: &'static [&'static str] = &["author"];
+        const DEFAULT_EXPAND: &'static [&'static str] = &["author"];
+    }
+
+    fn selection(fields: Option<&str>, expand: Option<&str>) -> Result<FieldSelection, String> {
+        FieldSelection::parse::<Article>(&FieldsQuery {
+            fields: fields.map(String::from),
+            expand: expand.map(String::from),
+        })
+    }
+
+    fn article() -> Value {
+        json!({"id": 1, "title": "T", "body": "B", "author": {"id": 2}, "rank": 0.5})
+    }
+
+    #[test]
+    fn no_selection_returns_everything_with_default_expansions() {
+        let selection = selection(None, None).unwrap();
+
+        assert_eq!(selection.project(&article()), article());
+    }
+
+    #[test]
+    fn fields_trim_the_response_and_keep_id_and_undeclared_keys() {
+        let selection = selection(Some(" title, "), None).unwrap();
+
+        assert!(selection.includes("id"));
+        assert!(!selection.includes("body"));
+        assert!(!selection.expands("author"));
+        assert_eq!(selection.project(&article()), json!({"id": 1, "title": "T", "rank": 0.5}));
+    }
+
+    #[test]
+    fn expand_alone_keeps_every_field() {
+        let selection = selection(None, Some("")).unwrap();
+
+        assert_eq!(
+            selection.project(&article()),
+            json!({"id": 1, "title": "T", "body": "B", "rank": 0.5})
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert_eq!(selection(Some("title,nope"), None).unwrap_err(), "Unknown field 'nope'");
+        assert_eq!(selection(None, Some("editor")).unwrap_err(), "Unknown expansion 'editor'");
+    }
+}
\ No newline at end of file
@@ -1,5 +1,35 @@
 pub mod user;
 pub mod post;
+pub mod pagination;
+pub mod fields;
+pub mod revision;
+pub mod comment;
+pub mod reaction;
+pub mod tag;
+pub mod attachment;
+pub mod upload;
+pub mod collaborator;
+pub mod stats;
+pub mod feed;
+pub mod moderation;
+pub mod transfer;
+pub mod bookmark;
+pub mod translation;
 
 pub use user::*;
-pub use post::*;
\ No newline at end of file
+pub use post::*;
+pub use pagination::*;
+pub use fields::*;
+pub use revision::*;
+pub use comment::*;
+pub use reaction::*;
+pub use tag::*;
+pub use attachment::*;
+pub use upload::*;
+pub use collaborator::*;
+pub use stats::*;
+pub use feed::*;
+pub use moderation::*;
+pub use transfer::*;
+pub use bookmark::*;
+pub use translation::*;
\ No newline at end of file
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "report_reason", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Harassment,
+    HateSpeech,
+    Violence,
+    SexualContent,
+    Misinformation,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "report_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    /// Waiting for a moderator.
+    #[default]
+    Open,
+    /// A moderator found nothing to act on.
+    Dismissed,
+    /// A moderator hid the post or suspended its author.
+    Actioned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "moderation_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationActionKind {
+    /// Closes the post's open reports without action, and unhides the post
+    /// if the report threshold hid it.
+    Dismiss,
+    HidePost,
+    SuspendAuthor,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Report {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub reporter_id: Uuid,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub status: ReportStatus,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A report in the moderation queue, with the reported post's state.
+#[derive(Debug, FromRow, Serialize)]
+pub struct QueuedReport {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub report: Report,
+    pub post_title: String,
+    pub post_user_id: Uuid,
+    pub post_hidden_at: Option<DateTime<Utc>>,
+    /// Open reports of the post, including this one if it is open.
+    pub post_open_reports: i64,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct ModerationAction {
+    pub id: Uuid,
+    pub post_id: Option<Uuid>,
+    pub report_id: Option<Uuid>,
+    pub target_user_id: Option<Uuid>,
+    /// `None` for posts hidden automatically by the report threshold.
+    pub moderator_id: Option<Uuid>,
+    pub action: ModerationActionKind,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateReportRequest {
+    pub reason: ReportReason,
+    #[validate(length(max = 2000, message = "Details must be at most 2000 characters"))]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ModerationActionRequest {
+    pub action: ModerationActionKind,
+    #[validate(length(max = 2000, message = "Note must be at most 2000 characters"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportQueueQuery {
+    #[serde(default)]
+    pub status: ReportStatus,
+    pub reason: Option<ReportReason>,
+    pub post_id: Option<Uuid>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationLogQuery {
+    pub post_id: Option<Uuid>,
+    pub moderator_id: Option<Uuid>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use actix_web::HttpRequest;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Position of the last row of a page in a `(sort key, id)` keyset.
+///
+/// `sort` names the ordering the cursor was issued for so that a cursor
+/// cannot be replayed against a different sort.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub sort: String,
+    pub key: String,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(sort: impl Into<String>, key: impl Into<String>, id: Uuid) -> Self {
+        Self {
+            sort: sort.into(),
+            key: key.into(),
+            id,
+        }
+    }
+
+    pub fn for_timestamp(sort: impl Into<String>, timestamp: DateTime<Utc>, id: Uuid) -> Self {
+        Self::new(sort, timestamp.to_rfc3339_opts(SecondsFormat::Micros, true), id)
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.key)
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    }
+
+    /// Encodes the cursor as an opaque, URL-safe token.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}|{}", self.sort, self.id, self.key))
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let mut parts = raw.splitn(3, '|');
+        let sort = parts.next()?;
+        let id = Uuid::parse_str(parts.next()?).ok()?;
+        let key = parts.next()?;
+
+        Some(Self::new(sort, key, id))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Resolves the requested page size against the server defaults.
+///
+/// Values above `max` are clamped; zero or negative values are rejected.
+pub fn resolve_limit(requested: Option<i64>, default: i64, max: i64) -> Result<i64, String> {
+    match requested {
+        None => Ok(default.min(max)),
+        Some(limit) if limit < 1 => Err("limit must be a positive integer".to_string()),
+        Some(limit) => Ok(limit.min(max)),
+    }
+}
+
+/// Builds an RFC 8288 `Link` header value pointing at the next page,
+/// preserving every query parameter except `cursor`.
+pub fn next_link(req: &HttpRequest, next_cursor: &str) -> String {
+    let connection = req.connection_info();
+    let mut params: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("cursor="))
+        .collect();
+    let cursor_param = format!("cursor={}", next_cursor);
+    params.push(&cursor_param);
+
+    format!(
+        "<{}://{}{}?{}>; rel=\"next\"",
+        connection.scheme(),
+        connection.host(),
+        req.path(),
+        params.join("&")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip_through_their_token() {
+        let cursor = Cursor::new("title:asc", "A | title with bars", Uuid::new_v4());
+        let token = cursor.encode();
+
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&token), Some(cursor));
+    }
+
+    #[test]
+    fn timestamp_cursors_keep_microseconds() {
+        let at = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        let cursor = Cursor::decode(&Cursor::for_timestamp("created_at", at, Uuid::new_v4()).encode()).unwrap();
+
+        assert_eq!(cursor.timestamp(), Some(at));
+    }
+
+    #[test]
+    fn malformed_tokens_are_rejected() {
+        assert_eq!(Cursor::decode("not base64!"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("created_at|not-a-uuid|key")), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("created_at")), None);
+    }
+
+    #[test]
+    fn limits_default_clamp_and_reject_non_positive_values() {
+        assert_eq!(resolve_limit(None, 20, 100), Ok(20));
+        assert_eq!(resolve_limit(None, 200, 100), Ok(100));
+        assert_eq!(resolve_limit(Some(50), 20, 100), Ok(50));
+        assert_eq!(resolve_limit(Some(500), 20, 100), Ok(100));
+        assert!(resolve_limit(Some(0), 20, 100).is_err());
+        assert!(resolve_limit(Some(-1), 20, 100).is_err());
+    }
+}
\ No newline at end of file
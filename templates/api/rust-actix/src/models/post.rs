@@ -1,27 +1,154 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors};
 
-use super::user::UserResponse;
+use super::{
+    bookmark::ViewerBookmarks,
+    collaborator::Collaborator,
+    fields::SparseFields,
+    pagination::Cursor,
+    reaction::{ReactionCounts, ViewerReactions},
+    tag::{normalize_tags, validate_tags, TagMatch},
+    translation::{validate_locale, PostTranslation},
+    user::UserResponse,
+};
+
+pub const MAX_SLUG_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "post_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    Scheduled,
+    Published,
+}
+
+impl PostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Published => "published",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "content_format", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+impl ContentFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Plain => "plain",
+            ContentFormat::Markdown => "markdown",
+        }
+    }
+}
 
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Post {
     pub id: Uuid,
     pub title: String,
+    pub slug: String,
+    pub custom_slug: bool,
     pub content: String,
+    pub content_format: ContentFormat,
+    pub content_html: String,
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+    pub locale: String,
     pub user_id: Uuid,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub comment_count: i32,
+    pub reaction_counts: Json<ReactionCounts>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_create_post_request"))]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 255, message = "Title must be between 1 and 255 characters"))]
     pub title: String,
     #[validate(length(min = 1, message = "Content is required"))]
     pub content: String,
+    #[serde(default)]
+    pub content_format: ContentFormat,
+    /// Canonical locale of the title and content; `DEFAULT_LOCALE` when absent.
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+    /// Custom slug; generated from the title when absent.
+    #[validate(custom(function = "validate_slug"))]
+    pub slug: Option<String>,
+    pub status: Option<PostStatus>,
+    pub publish_at: Option<DateTime<Utc>>,
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+}
+
+impl CreatePostRequest {
+    /// Status and publication time to store; posts are published
+    /// immediately unless another status is requested.
+    pub fn publication(&self) -> (PostStatus, Option<DateTime<Utc>>) {
+        match self.status.unwrap_or(PostStatus::Published) {
+            PostStatus::Published => (PostStatus::Published, Some(Utc::now())),
+            PostStatus::Scheduled => (PostStatus::Scheduled, self.publish_at),
+            PostStatus::Draft => (PostStatus::Draft, None),
+        }
+    }
+}
+
+fn validate_create_post_request(request: &CreatePostRequest) -> Result<(), ValidationError> {
+    match (request.status, request.publish_at) {
+        (Some(PostStatus::Scheduled), None) => Err(ValidationError::new("publish_at")
+            .with_message("publish_at is required for scheduled posts".into())),
+        (Some(PostStatus::Scheduled), Some(publish_at)) if publish_at <= Utc::now() => {
+            Err(ValidationError::new("publish_at")
+                .with_message("publish_at must be in the future".into()))
+        }
+        (Some(PostStatus::Scheduled), Some(_)) | (_, None) => Ok(()),
+        (_, Some(_)) => Err(ValidationError::new("publish_at")
+            .with_message("publish_at is only allowed for scheduled posts".into())),
+    }
+}
+
+/// Custom slugs use the same alphabet as generated ones: lowercase ASCII
+/// letters and digits separated by single hyphens.
+fn validate_slug(slug: &str) -> Result<(), ValidationError> {
+    let valid = !slug.is_empty()
+        && slug.len() <= MAX_SLUG_LENGTH
+        && slug
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("slug").with_message(
+            format!(
+                "Slug must be at most {} lowercase letters, digits and single hyphens",
+                MAX_SLUG_LENGTH
+            )
+            .into(),
+        ))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PublishPostRequest {
+    /// Publishes immediately when absent or not in the future.
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -30,54 +157,480 @@ pub struct UpdatePostRequest {
     pub title: Option<String>,
     #[validate(length(min = 1, message = "Content cannot be empty"))]
     pub content: Option<String>,
+    pub content_format: Option<ContentFormat>,
+    /// Sets a custom slug, which then no longer follows title changes.
+    #[validate(custom(function = "validate_slug"))]
+    pub slug: Option<String>,
+    /// Replaces the post's tags when present.
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+}
+
+/// The editable fields of a post, as `PATCH /posts/{id}` patches see them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PostDocument {
+    pub title: String,
+    pub content: String,
+    pub content_format: ContentFormat,
+    pub slug: String,
+    pub tags: Vec<String>,
+}
+
+impl PostDocument {
+    pub fn new(post: &Post, tags: Vec<String>) -> Self {
+        Self {
+            title: post.title.clone(),
+            content: post.content.clone(),
+            content_format: post.content_format,
+            slug: post.slug.clone(),
+            tags,
+        }
+    }
+
+    /// The update that turns `original` into this document, so that a patch
+    /// goes through the same validation and write as `PUT`. Unchanged
+    /// fields are left out; in particular an unchanged slug stays generated.
+    pub fn changes_from(self, original: &PostDocument) -> UpdatePostRequest {
+        UpdatePostRequest {
+            title: (self.title != original.title).then_some(self.title),
+            content: (self.content != original.content).then_some(self.content),
+            content_format: (self.content_format != original.content_format).then_some(self.content_format),
+            slug: (self.slug != original.slug).then_some(self.slug),
+            tags: (self.tags != original.tags).then_some(self.tags),
+        }
+    }
+}
+
+/// How `POST /posts/batch` treats operations that are refused.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    /// The first refused operation rolls back the whole batch.
+    #[default]
+    Atomic,
+    /// Refused operations are reported and skipped; the others are committed.
+    PerItem,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchPostRequest {
+    #[serde(default)]
+    pub mode: BatchMode,
+    pub operations: Vec<BatchOperation>,
+}
+
+/// One write of a batch. `if_match` takes the place of the `If-Match`
+/// header of the single-post endpoints.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Create {
+        data: CreatePostRequest,
+    },
+    Update {
+        id: Uuid,
+        data: UpdatePostRequest,
+        if_match: Option<String>,
+    },
+    Delete {
+        id: Uuid,
+        if_match: Option<String>,
+    },
+}
+
+impl BatchOperation {
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            BatchOperation::Create { data } => data.validate(),
+            BatchOperation::Update { data, .. } => data.validate(),
+            BatchOperation::Delete { .. } => Ok(()),
+        }
+    }
+}
+
+/// Outcome of one batch operation, with the status and body its
+/// single-post endpoint would have answered with.
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub index: usize,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<PostResponse>,
+    /// Error body of a refused operation, e.g. `{"error": "Post not found"}`.
+    #[serde(flatten)]
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Title,
+}
+
+impl PostSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostSortField::CreatedAt => "created_at",
+            PostSortField::UpdatedAt => "updated_at",
+            PostSortField::Title => "title",
+        }
+    }
+
+    /// Whether `cursor` was issued for this sort order and direction and
+    /// carries a usable key.
+    pub fn accepts(&self, direction: SortDirection, cursor: &Cursor) -> bool {
+        cursor.sort == self.cursor_sort(direction)
+            && (*self == PostSortField::Title || cursor.timestamp().is_some())
+    }
+
+    /// Keyset cursor pointing just past `post` for this sort order.
+    pub fn cursor_for(&self, direction: SortDirection, post: &PostWithUser) -> Cursor {
+        let sort = self.cursor_sort(direction);
+        match self {
+            PostSortField::CreatedAt => Cursor::for_timestamp(sort, post.created_at, post.id),
+            PostSortField::UpdatedAt => Cursor::for_timestamp(sort, post.updated_at, post.id),
+            PostSortField::Title => Cursor::new(sort, post.title.clone(), post.id),
+        }
+    }
+
+    /// A cursor resumes one direction only; replayed against the other it
+    /// would skip the rows before it.
+    fn cursor_sort(&self, direction: SortDirection) -> String {
+        format!("{}:{}", self.as_str(), direction.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    /// Comparison operator that selects rows after the cursor.
+    pub fn keyset_operator(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+#[validate(schema(function = "validate_post_list_query"))]
+pub struct PostListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    pub author_id: Option<Uuid>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    #[validate(length(min = 1, max = 255, message = "Title filter must be between 1 and 255 characters"))]
+    pub title: Option<String>,
+    pub status: Option<PostStatus>,
+    /// Comma-separated list of tags.
+    #[validate(length(min = 1, max = 255, message = "Tag filter must be between 1 and 255 characters"))]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    #[serde(default)]
+    pub sort: PostSortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+impl PostListQuery {
+    /// Normalized tags of the `?tag=` filter.
+    pub fn tags(&self) -> Vec<String> {
+        match &self.tag {
+            Some(tag) => normalize_tags(&tag.split(',').collect::<Vec<_>>()),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn validate_post_list_query(query: &PostListQuery) -> Result<(), ValidationError> {
+    if let (Some(after), Some(before)) = (query.created_after, query.created_before) {
+        if after > before {
+            return Err(ValidationError::new("created_range")
+                .with_message("created_after must not be later than created_before".into()));
+        }
+    }
+
+    if let (Some(after), Some(before)) = (query.updated_after, query.updated_before) {
+        if after > before {
+            return Err(ValidationError::new("updated_range")
+                .with_message("updated_after must not be later than updated_before".into()));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PostSearchQuery {
+    #[validate(length(min = 1, max = 256, message = "Search query must be between 1 and 256 characters"))]
+    pub q: String,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct PostResponse {
     pub id: Uuid,
     pub title: String,
+    pub slug: String,
     pub content: String,
+    pub content_format: ContentFormat,
+    /// Sanitized HTML rendered from `content`.
+    pub content_html: String,
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+    /// Locale the title and content are served in.
+    pub locale: String,
+    /// Locale the post was written in.
+    pub canonical_locale: String,
+    /// Further locales the post is translated into.
+    pub translations: Vec<String>,
     pub user_id: Uuid,
     pub user: Option<UserResponse>,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    /// Users the author shared the post with.
+    pub collaborators: Vec<Collaborator>,
+    pub comment_count: i32,
+    pub reaction_counts: ReactionCounts,
+    /// Kinds the signed-in viewer reacted with; absent for anonymous requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub viewer_reactions: Option<Vec<String>>,
+    /// Whether the signed-in viewer bookmarked the post; absent for
+    /// anonymous requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmarked: Option<bool>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Set while moderation hides the post from everyone but its author and
+    /// collaborators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, FromRow)]
+/// A post with its author. Columns marked `default` may be left out of
+/// queries whose field selection does not need them (see `PostColumns`).
+#[derive(Debug, Clone, FromRow)]
 pub struct PostWithUser {
     pub id: Uuid,
     pub title: String,
+    pub slug: String,
+    #[sqlx(default)]
     pub content: String,
+    pub content_format: ContentFormat,
+    #[sqlx(default)]
+    pub content_html: String,
+    #[sqlx(default)]
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+    pub locale: String,
+    pub translations: Vec<String>,
     pub user_id: Uuid,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
+    pub tags: Vec<String>,
+    #[sqlx(default)]
+    pub collaborators: Json<Vec<Collaborator>>,
+    pub comment_count: i32,
+    #[sqlx(default)]
+    pub reaction_counts: Json<ReactionCounts>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub hidden_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
     pub user_email: String,
+    #[sqlx(default)]
     pub user_first_name: String,
+    #[sqlx(default)]
     pub user_last_name: String,
+    #[sqlx(default)]
     pub user_created_at: DateTime<Utc>,
+    #[sqlx(default)]
     pub user_updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, FromRow)]
+pub struct PostSearchRow {
+    #[sqlx(flatten)]
+    pub post: PostWithUser,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub content_snippet: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHighlights {
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostSearchResult {
+    #[serde(flatten)]
+    pub post: PostResponse,
+    pub rank: f32,
+    pub highlights: SearchHighlights,
+}
+
 impl From<Post> for PostResponse {
     fn from(post: Post) -> Self {
         Self {
             id: post.id,
             title: post.title,
+            slug: post.slug,
             content: post.content,
+            content_format: post.content_format,
+            content_html: post.content_html,
+            excerpt: post.excerpt,
+            reading_time_minutes: post.reading_time_minutes,
+            locale: post.locale.clone(),
+            canonical_locale: post.locale,
+            translations: Vec::new(),
             user_id: post.user_id,
             user: None,
+            status: post.status,
+            publish_at: post.publish_at,
+            tags: Vec::new(),
+            collaborators: Vec::new(),
+            comment_count: post.comment_count,
+            reaction_counts: post.reaction_counts.0,
+            viewer_reactions: None,
+            bookmarked: None,
             created_at: post.created_at,
             updated_at: post.updated_at,
+            deleted_at: post.deleted_at,
+            hidden_at: None,
         }
     }
 }
 
+impl PostResponse {
+    /// Fills in `viewer_reactions` for an authenticated request; `None`
+    /// leaves the field out of the response.
+    pub fn with_viewer_reactions(mut self, reactions: Option<&ViewerReactions>) -> Self {
+        self.viewer_reactions =
+            reactions.map(|reactions| reactions.get(&self.id).cloned().unwrap_or_default());
+        self
+    }
+
+    /// Fills in `bookmarked` for an authenticated request; `None` leaves the
+    /// field out of the response.
+    pub fn with_viewer_bookmarks(mut self, bookmarks: Option<&ViewerBookmarks>) -> Self {
+        self.bookmarked = bookmarks.map(|bookmarks| bookmarks.contains(&self.id));
+        self
+    }
+
+    /// Serves the title and content of `translation` instead of the
+    /// canonical ones; `None` keeps the canonical locale.
+    pub fn with_translation(mut self, translation: Option<PostTranslation>) -> Self {
+        if let Some(translation) = translation {
+            self.locale = translation.locale;
+            self.title = translation.title;
+            self.content = translation.content;
+            self.content_html = translation.content_html;
+            self.excerpt = translation.excerpt;
+            self.reading_time_minutes = translation.reading_time_minutes;
+        }
+        self
+    }
+}
+
+impl SparseFields for PostResponse {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "slug",
+        "content",
+        "content_format",
+        "content_html",
+        "excerpt",
+        "reading_time_minutes",
+        "locale",
+        "canonical_locale",
+        "translations",
+        "user_id",
+        "status",
+        "publish_at",
+        "tags",
+        "collaborators",
+        "comment_count",
+        "reaction_counts",
+        "viewer_reactions",
+        "bookmarked",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+        "hidden_at",
+    ];
+    const EXPANSIONS: &'static [&'static str] = &["user"];
+    const DEFAULT_EXPAND: &'static [&'static str] = &["user"];
+}
+
+impl PostWithUser {
+    /// Published posts are public unless moderation hid them; anything else
+    /// is only visible to its author and the users they shared it with.
+    pub fn is_visible_to(&self, viewer: Option<Uuid>) -> bool {
+        (self.status == PostStatus::Published && self.hidden_at.is_none())
+            || viewer.is_some_and(|viewer| {
+                viewer == self.user_id
+                    || self.collaborators.iter().any(|collaborator| collaborator.user_id == viewer)
+            })
+    }
+}
+
 impl From<PostWithUser> for PostResponse {
     fn from(post_with_user: PostWithUser) -> Self {
         Self {
             id: post_with_user.id,
             title: post_with_user.title,
+            slug: post_with_user.slug,
             content: post_with_user.content,
+            content_format: post_with_user.content_format,
+            content_html: post_with_user.content_html,
+            excerpt: post_with_user.excerpt,
+            reading_time_minutes: post_with_user.reading_time_minutes,
+            locale: post_with_user.locale.clone(),
+            canonical_locale: post_with_user.locale,
+            translations: post_with_user.translations,
             user_id: post_with_user.user_id,
             user: Some(UserResponse {
                 id: post_with_user.user_id,
@@ -87,8 +640,55 @@ impl From<PostWithUser> for PostResponse {
                 created_at: post_with_user.user_created_at,
                 updated_at: post_with_user.user_updated_at,
             }),
+            status: post_with_user.status,
+            publish_at: post_with_user.publish_at,
+            tags: post_with_user.tags,
+            collaborators: post_with_user.collaborators.0,
+            comment_count: post_with_user.comment_count,
+            reaction_counts: post_with_user.reaction_counts.0,
+            viewer_reactions: None,
+            bookmarked: None,
             created_at: post_with_user.created_at,
             updated_at: post_with_user.updated_at,
+            deleted_at: post_with_user.deleted_at,
+            hidden_at: post_with_user.hidden_at,
         }
     }
+}
+
+impl From<PostSearchRow> for PostSearchResult {
+    fn from(row: PostSearchRow) -> Self {
+        Self {
+            post: PostResponse::from(row.post),
+            rank: row.rank,
+            highlights: SearchHighlights {
+                title: row.title_highlight,
+                content: row.content_snippet,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_only_resume_the_sort_and_direction_they_were_issued_for() {
+        let cursor = Cursor::for_timestamp("created_at:desc", Utc::now(), Uuid::new_v4());
+
+        assert!(PostSortField::CreatedAt.accepts(SortDirection::Desc, &cursor));
+        assert!(!PostSortField::CreatedAt.accepts(SortDirection::Asc, &cursor));
+        assert!(!PostSortField::UpdatedAt.accepts(SortDirection::Desc, &cursor));
+    }
+
+    #[test]
+    fn timestamp_sorts_need_a_timestamp_key() {
+        let cursor = Cursor::new("created_at:asc", "not a timestamp", Uuid::new_v4());
+
+        assert!(!PostSortField::CreatedAt.accepts(SortDirection::Asc, &cursor));
+
+        let cursor = Cursor::new("title:asc", "Any title", Uuid::new_v4());
+        assert!(PostSortField::Title.accepts(SortDirection::Asc, &cursor));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Number of reactions per kind, e.g. `{"like": 3}`. Kinds nobody has used
+/// are left out.
+pub type ReactionCounts = BTreeMap<String, i64>;
+
+/// Reaction kinds the signed-in viewer left, keyed by post.
+pub type ViewerReactions = HashMap<Uuid, Vec<String>>;
+
+#[derive(Debug, Serialize)]
+pub struct ReactionSummary {
+    pub post_id: Uuid,
+    pub reaction_counts: ReactionCounts,
+    pub viewer_reactions: Vec<String>,
+}
\ No newline at end of file
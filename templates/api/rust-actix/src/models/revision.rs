@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::post::ContentFormat;
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PostRevision {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    pub content_format: ContentFormat,
+    pub editor_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct PostRevisionSummary {
+    pub revision: i32,
+    pub title: String,
+    pub editor_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionDiffResponse {
+    pub from: i32,
+    pub to: i32,
+    /// Unified diff of the title; empty when unchanged.
+    pub title_diff: String,
+    /// Line-level unified diff of the content; empty when unchanged.
+    pub content_diff: String,
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Date range for post stats, inclusive and in UTC. Defaults to the last
+/// 30 days.
+#[derive(Debug, Deserialize)]
+pub struct PostStatsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct DailyViews {
+    pub date: NaiveDate,
+    pub views: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostStats {
+    pub post_id: Uuid,
+    /// All-time views, not just those in the range.
+    pub total_views: i64,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// One entry per day in the range, including days without views.
+    pub daily: Vec<DailyViews>,
+}
\ No newline at end of file
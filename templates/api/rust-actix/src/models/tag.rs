@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::ValidationError;
+
+pub const MAX_TAGS_PER_POST: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 50;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub post_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagListQuery {
+    pub limit: Option<i64>,
+}
+
+/// How a `?tag=` filter with several tags is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Posts carrying at least one of the tags.
+    #[default]
+    Any,
+    /// Posts carrying every one of the tags.
+    All,
+}
+
+/// Canonical form of a tag: lowercase, with runs of whitespace replaced by
+/// a single hyphen, so `"Web  Dev"` and `"web-dev"` are the same tag.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Normalizes, sorts and deduplicates a list of tags.
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| normalize_tag(tag.as_ref()))
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+pub fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if normalize_tags(tags).len() > MAX_TAGS_PER_POST {
+        return Err(ValidationError::new("tags")
+            .with_message(format!("A post can have at most {} tags", MAX_TAGS_PER_POST).into()));
+    }
+
+    for tag in tags {
+        let normalized = normalize_tag(tag);
+        if normalized.is_empty() || normalized.chars().count() > MAX_TAG_LENGTH {
+            return Err(ValidationError::new("tags").with_message(
+                format!("Tags must be between 1 and {} characters", MAX_TAG_LENGTH).into(),
+            ));
+        }
+        // Commas separate tags in the ?tag= filter
+        if normalized.contains(',') {
+            return Err(ValidationError::new("tags").with_message("Tags cannot contain commas".into()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
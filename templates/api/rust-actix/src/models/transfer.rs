@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{ContentFormat, CreatePostRequest, PostStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One JSON object per line, importable with `POST /posts/import`.
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// A post as exported, one per NDJSON line or CSV row.
+#[derive(Debug, FromRow, Serialize)]
+pub struct ExportedPost {
+    pub id: Uuid,
+    pub title: String,
+    pub slug: String,
+    pub content: String,
+    pub content_format: ContentFormat,
+    pub locale: String,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub author_email: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One NDJSON line of an import. Exported lines are accepted as they are;
+/// `id` and `updated_at` are ignored, and `author_email` only counts for
+/// admins.
+#[derive(Debug, Deserialize)]
+pub struct ImportedPost {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub content_format: ContentFormat,
+    pub locale: Option<String>,
+    pub slug: Option<String>,
+    pub status: Option<PostStatus>,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub tags: Option<Vec<String>>,
+    pub author_email: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl ImportedPost {
+    /// The create request the post is written with. Published posts keep
+    /// their original `publish_at`, which is restored after the insert.
+    pub fn to_create_request(&self) -> CreatePostRequest {
+        CreatePostRequest {
+            title: self.title.clone(),
+            content: self.content.clone(),
+            content_format: self.content_format,
+            locale: self.locale.clone(),
+            slug: self.slug.clone(),
+            status: self.status,
+            publish_at: self.publish_at.filter(|_| self.status == Some(PostStatus::Scheduled)),
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+/// Outcome of `POST /posts/import`.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    /// Non-empty lines read.
+    pub lines: usize,
+    pub imported: usize,
+    pub failed: usize,
+    /// The first failures, in line order.
+    pub errors: Vec<ImportError>,
+    /// Whether more lines failed than `errors` lists.
+    pub errors_truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportError {
+    /// 1-based line number in the request body.
+    pub line: usize,
+    pub status: u16,
+    /// Error body the single-post endpoint would have answered with.
+    #[serde(flatten)]
+    pub error: Option<serde_json::Value>,
+}
\ No newline at end of file
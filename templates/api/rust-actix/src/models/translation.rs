@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Longest language tag stored (RFC 5646, section 4.4.1).
+pub const MAX_LOCALE_LENGTH: usize = 35;
+
+/// Translations to serve, by post id.
+pub type PostTranslations = HashMap<Uuid, PostTranslation>;
+
+/// A post's title and content in a locale other than its canonical one,
+/// rendered in the post's content format.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PostTranslation {
+    pub post_id: Uuid,
+    pub locale: String,
+    pub title: String,
+    pub content: String,
+    /// Sanitized HTML rendered from `content`.
+    pub content_html: String,
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TranslationRequest {
+    #[validate(length(min = 1, max = 255, message = "Title must be between 1 and 255 characters"))]
+    pub title: String,
+    #[validate(length(min = 1, message = "Content is required"))]
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocaleQuery {
+    /// Locale to read posts in; takes precedence over `Accept-Language`.
+    pub lang: Option<String>,
+}
+
+/// Normalizes a BCP 47 language tag to its conventional case (`zh-Hant-TW`,
+/// `pt-BR`), accepting `_` as a separator. Returns `None` for anything that
+/// is not a well-formed tag.
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    if tag.is_empty() || tag.len() > MAX_LOCALE_LENGTH {
+        return None;
+    }
+
+    let mut normalized = Vec::new();
+    // Extension and private use subtags after a singleton are lowercase
+    let mut in_extension = false;
+
+    for (index, subtag) in tag.split(['-', '_']).enumerate() {
+        if subtag.is_empty() || subtag.len() > 8 || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        let subtag = if index == 0 {
+            if subtag.len() < 2 || !subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+                return None;
+            }
+            subtag.to_ascii_lowercase()
+        } else if in_extension {
+            subtag.to_ascii_lowercase()
+        } else if subtag.len() == 1 {
+            in_extension = true;
+            subtag.to_ascii_lowercase()
+        } else if subtag.len() == 2 {
+            // Region
+            subtag.to_ascii_uppercase()
+        } else if subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+            // Script
+            let lower = subtag.to_ascii_lowercase();
+            lower[..1].to_ascii_uppercase() + &lower[1..]
+        } else {
+            subtag.to_ascii_lowercase()
+        };
+        normalized.push(subtag);
+    }
+
+    Some(normalized.join("-"))
+}
+
+pub fn validate_locale(locale: &str) -> Result<(), ValidationError> {
+    match normalize_locale(locale) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("locale")
+            .with_message("Locale must be a language tag such as en or pt-BR".into())),
+    }
+}
+
+/// Picks the locale to serve a post in, trying the requested locales in
+/// order of preference and falling back to `canonical`. For each requested
+/// locale the first of these that is available wins:
+///
+/// 1. the locale itself;
+/// 2. a shorter form of it (`de-AT` → `de`);
+/// 3. a locale with the same language (`de` → `de-CH`), preferring the
+///    canonical one.
+///
+/// `*` stands for the canonical locale. Locales compare case-insensitively.
+pub fn negotiate_locale<'a>(requested: &[String], canonical: &'a str, translations: &'a [String]) -> &'a str {
+    let available = || std::iter::once(canonical).chain(translations.iter().map(String::as_str));
+
+    for locale in requested {
+        if locale == "*" {
+            return canonical;
+        }
+
+        let mut prefix = locale.as_str();
+        loop {
+            if let Some(found) = available().find(|candidate| candidate.eq_ignore_ascii_case(prefix)) {
+                return found;
+            }
+            match prefix.rfind('-') {
+                Some(end) => prefix = &prefix[..end],
+                None => break,
+            }
+        }
+
+        if let Some(found) = available().find(|candidate| language(candidate).eq_ignore_ascii_case(prefix)) {
+            return found;
+        }
+    }
+
+    canonical
+}
+
+/// Primary language subtag of a language tag.
+fn language(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locales(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn locales_are_normalized_to_conventional_case() {
+        assert_eq!(normalize_locale("EN").as_deref(), Some("en"));
+        assert_eq!(normalize_locale("pt_br").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize_locale("zh-hant-tw").as_deref(), Some("zh-Hant-TW"));
+        assert_eq!(normalize_locale("es-419").as_deref(), Some("es-419"));
+        assert_eq!(normalize_locale("de-x-DE").as_deref(), Some("de-x-de"));
+    }
+
+    #[test]
+    fn malformed_locales_are_rejected() {
+        for tag in ["", "e", "1a", "en-", "en--us", "en us", "en-toolongsubtag", &"a".repeat(36)] {
+            assert_eq!(normalize_locale(tag), None, "{:?}", tag);
+        }
+    }
+
+    #[test]
+    fn negotiation_prefers_exact_then_shorter_then_same_language() {
+        let translations = locales(&["de", "fr-CA"]);
+
+        assert_eq!(negotiate_locale(&locales(&["de"]), "en", &translations), "de");
+        assert_eq!(negotiate_locale(&locales(&["de-AT"]), "en", &translations), "de");
+        assert_eq!(negotiate_locale(&locales(&["fr-FR"]), "en", &translations), "fr-CA");
+        assert_eq!(negotiate_locale(&locales(&["en-GB"]), "en", &translations), "en");
+    }
+
+    #[test]
+    fn negotiation_follows_preference_order_and_falls_back_to_canonical() {
+        let translations = locales(&["de", "fr"]);
+
+        assert_eq!(negotiate_locale(&locales(&["it", "fr", "de"]), "en", &translations), "fr");
+        assert_eq!(negotiate_locale(&locales(&["*", "de"]), "en", &translations), "en");
+        assert_eq!(negotiate_locale(&locales(&["it"]), "en", &translations), "en");
+        assert_eq!(negotiate_locale(&[], "en", &translations), "en");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A tus upload and how far it has got.
+#[derive(Debug, Clone, FromRow)]
+pub struct ResumableUpload {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub post_id: Uuid,
+    pub filename: String,
+    pub declared_type: Option<String>,
+    pub private: bool,
+    pub metadata: String,
+    pub upload_length: i64,
+    pub upload_offset: i64,
+    pub attachment_id: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ResumableUpload {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Parses a tus `Upload-Metadata` header: comma-separated pairs of a key
+/// and an optional base64-encoded value, e.g. `filename d29ybGQ=,private`.
+pub fn parse_upload_metadata(header: &str) -> Result<HashMap<String, String>, String> {
+    let mut metadata = HashMap::new();
+
+    for pair in header.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let mut parts = pair.split(' ');
+        let key = parts.next().unwrap_or_default();
+        let value = match parts.next() {
+            Some(encoded) => STANDARD
+                .decode(encoded)
+                .ok()
+                .and_then(|value| String::from_utf8(value).ok())
+                .ok_or_else(|| format!("Metadata value of '{}' is not valid base64 UTF-8", key))?,
+            None => String::new(),
+        };
+
+        if parts.next().is_some() {
+            return Err(format!("Malformed metadata pair '{}'", pair));
+        }
+        if metadata.insert(key.to_string(), value).is_some() {
+            return Err(format!("Duplicate metadata key '{}'", key));
+        }
+    }
+
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_are_decoded_and_values_are_optional() {
+        let metadata = parse_upload_metadata("filename d29ybGQucG5n, private").unwrap();
+
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["filename"], "world.png");
+        assert_eq!(metadata["private"], "");
+    }
+
+    #[test]
+    fn empty_headers_have_no_metadata() {
+        assert!(parse_upload_metadata("").unwrap().is_empty());
+        assert!(parse_upload_metadata(" , ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_pairs_are_rejected() {
+        assert_eq!(
+            parse_upload_metadata("filename !!!").unwrap_err(),
+            "Metadata value of 'filename' is not valid base64 UTF-8"
+        );
+        assert_eq!(
+            parse_upload_metadata("filename d29ybGQ= extra").unwrap_err(),
+            "Malformed metadata pair 'filename d29ybGQ= extra'"
+        );
+        assert_eq!(
+            parse_upload_metadata("private,private").unwrap_err(),
+            "Duplicate metadata key 'private'"
+        );
+    }
+}
\ No newline at end of file
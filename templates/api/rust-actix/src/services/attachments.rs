@@ -0,0 +1,344 @@
+use std::io::Cursor;
+
+use bytes::Bytes;
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use hmac::{Hmac, Mac};
+use image::{ImageFormat, ImageReader, Limits};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{Attachment, AttachmentResponse, AttachmentVariant, DownloadQuery},
+    storage::{StorageBackend, StorageError},
+};
+
+/// Base path of the download routes.
+const DOWNLOAD_PATH: &str = "/api/v1/attachments";
+/// Images wider or taller than this are rejected rather than decoded.
+const MAX_IMAGE_DIMENSION: u32 = 16_384;
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/png";
+
+const ATTACHMENT_SELECT: &str = r#"
+    SELECT
+        a.id, a.post_id, a.user_id, a.sha256, a.filename, a.private, a.created_at,
+        b.storage_key, b.thumbnail_key, b.content_type, b.size_bytes, b.width, b.height
+    FROM attachments a
+    JOIN attachment_blobs b ON b.sha256 = a.sha256
+"#;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttachmentError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("File type '{0}' is not allowed")]
+    DisallowedType(String),
+    #[error("File is not a valid image: {0}")]
+    InvalidImage(#[from] image::ImageError),
+}
+
+/// What is learned about an upload before it is stored.
+pub struct ProcessedUpload {
+    pub sha256: String,
+    pub content_type: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub thumbnail: Option<Bytes>,
+}
+
+/// Identifies, hashes and thumbnails an uploaded file. This is CPU-bound,
+/// so callers should run it on a blocking thread.
+pub fn process_upload(
+    data: &[u8],
+    declared_type: Option<&str>,
+    allowed_types: &[String],
+    thumbnail_size: u32,
+) -> Result<ProcessedUpload, UploadError> {
+    // Trust the file's magic bytes over the client's Content-Type where known
+    let content_type = infer::get(data)
+        .map(|kind| kind.mime_type().to_string())
+        .or_else(|| declared_type.map(str::to_string))
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    if !allowed_types.contains(&content_type) {
+        return Err(UploadError::DisallowedType(content_type));
+    }
+
+    let image_format = ImageFormat::from_mime_type(&content_type).filter(ImageFormat::reading_enabled);
+    let (width, height, thumbnail) = match image_format {
+        Some(format) => {
+            let (width, height, thumbnail) = make_thumbnail(data, format, thumbnail_size)?;
+            (Some(width as i32), Some(height as i32), Some(thumbnail))
+        }
+        None => (None, None, None),
+    };
+
+    Ok(ProcessedUpload {
+        sha256: hex::encode(Sha256::digest(data)),
+        content_type,
+        width,
+        height,
+        thumbnail,
+    })
+}
+
+/// Decodes an image and scales it to fit in a `size` square, returning the
+/// original dimensions and the thumbnail as PNG.
+fn make_thumbnail(data: &[u8], format: ImageFormat, size: u32) -> Result<(u32, u32, Bytes), image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    let mut thumbnail = Cursor::new(Vec::new());
+    image.thumbnail(size, size).write_to(&mut thumbnail, ImageFormat::Png)?;
+
+    Ok((image.width(), image.height(), thumbnail.into_inner().into()))
+}
+
+pub struct AttachmentService<'a> {
+    pool: &'a PgPool,
+    storage: &'a dyn StorageBackend,
+}
+
+impl<'a> AttachmentService<'a> {
+    pub fn new(pool: &'a PgPool, storage: &'a dyn StorageBackend) -> Self {
+        Self { pool, storage }
+    }
+
+    /// Stores an uploaded file and attaches it to a post. Content that is
+    /// already stored is linked to rather than uploaded again.
+    pub async fn create(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        filename: &str,
+        private: bool,
+        data: Bytes,
+        upload: ProcessedUpload,
+    ) -> Result<Attachment, AttachmentError> {
+        let storage_key = format!("blobs/{}/{}", &upload.sha256[..2], upload.sha256);
+        let thumbnail_key = upload
+            .thumbnail
+            .as_ref()
+            .map(|_| format!("thumbnails/{}/{}.png", &upload.sha256[..2], upload.sha256));
+
+        // Touching the blob first keeps the sweeper off it while it is linked
+        sqlx::query!(
+            r#"
+            INSERT INTO attachment_blobs (
+                sha256, storage_key, thumbnail_key, content_type, size_bytes, width, height
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (sha256) DO UPDATE SET last_used_at = NOW()
+            "#,
+            upload.sha256,
+            storage_key,
+            thumbnail_key,
+            upload.content_type,
+            data.len() as i64,
+            upload.width,
+            upload.height
+        )
+        .execute(self.pool)
+        .await?;
+
+        if !self.storage.exists(&storage_key).await? {
+            self.storage.put(&storage_key, data, &upload.content_type).await?;
+        }
+        if let (Some(key), Some(thumbnail)) = (&thumbnail_key, upload.thumbnail) {
+            if !self.storage.exists(key).await? {
+                self.storage.put(key, thumbnail, THUMBNAIL_CONTENT_TYPE).await?;
+            }
+        }
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO attachments (post_id, user_id, sha256, filename, private)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            post_id,
+            user_id,
+            upload.sha256,
+            filename,
+            private
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        self.find(id).await?.ok_or(AttachmentError::Database(sqlx::Error::RowNotFound))
+    }
+
+    pub async fn find(&self, id: Uuid) -> Result<Option<Attachment>, sqlx::Error> {
+        sqlx::query_as::<_, Attachment>(&format!("{} WHERE a.id = $1", ATTACHMENT_SELECT))
+            .bind(id)
+            .fetch_optional(self.pool)
+            .await
+    }
+
+    /// Attachments of a post, oldest first.
+    pub async fn list_for_post(
+        &self,
+        post_id: Uuid,
+        include_private: bool,
+    ) -> Result<Vec<Attachment>, sqlx::Error> {
+        sqlx::query_as::<_, Attachment>(&format!(
+            "{} WHERE a.post_id = $1 AND (NOT a.private OR $2) ORDER BY a.created_at, a.id",
+            ATTACHMENT_SELECT
+        ))
+        .bind(post_id)
+        .bind(include_private)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Removes an attachment. Its file stays in storage until the sweeper
+    /// finds it unused.
+    pub async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query!("DELETE FROM attachments WHERE id = $1", id)
+            .execute(self.pool)
+            .await
+            .map(|result| result.rows_affected() > 0)
+    }
+
+    /// Deletes stored files that no attachment has used for `grace_secs`.
+    pub async fn sweep_unused_blobs(&self, grace_secs: i32) -> Result<u64, AttachmentError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Locked until commit, so an upload of the same content waits and
+        // then stores the file afresh
+        let blobs = sqlx::query!(
+            r#"
+            SELECT sha256, storage_key, thumbnail_key
+            FROM attachment_blobs b
+            WHERE b.last_used_at < NOW() - make_interval(secs => $1)
+              AND NOT EXISTS (SELECT 1 FROM attachments a WHERE a.sha256 = b.sha256)
+            LIMIT 100
+            FOR UPDATE SKIP LOCKED
+            "#,
+            grace_secs as f64
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for blob in &blobs {
+            self.storage.delete(&blob.storage_key).await?;
+            if let Some(thumbnail_key) = &blob.thumbnail_key {
+                self.storage.delete(thumbnail_key).await?;
+            }
+        }
+
+        let hashes: Vec<String> = blobs.into_iter().map(|blob| blob.sha256).collect();
+        let deleted = sqlx::query!("DELETE FROM attachment_blobs WHERE sha256 = ANY($1)", &hashes)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+}
+
+/// Storage key of one of an attachment's files, if it has that file.
+pub fn storage_key(attachment: &Attachment, variant: AttachmentVariant) -> Option<&str> {
+    match variant {
+        AttachmentVariant::Original => Some(&attachment.storage_key),
+        AttachmentVariant::Thumbnail => attachment.thumbnail_key.as_deref(),
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Issues download URLs, either plain or signed and time-limited.
+pub struct DownloadSigner<'a> {
+    key: &'a [u8],
+    ttl: Duration,
+}
+
+impl<'a> DownloadSigner<'a> {
+    pub fn new(key: &'a str, ttl_secs: u64) -> Self {
+        Self {
+            key: key.as_bytes(),
+            ttl: Duration::seconds(ttl_secs as i64),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn mac(&self, id: Uuid, variant: AttachmentVariant, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}:{}", id, variant.as_str(), expires).as_bytes());
+        mac
+    }
+
+    /// Checks a download URL's signature, returning when it expires if it is valid.
+    pub fn verify(
+        &self,
+        id: Uuid,
+        variant: AttachmentVariant,
+        query: &DownloadQuery,
+    ) -> Option<DateTime<Utc>> {
+        let expires = DateTime::from_timestamp(query.expires?, 0)?;
+        if expires <= Utc::now() {
+            return None;
+        }
+
+        let signature = hex::decode(query.signature.as_ref()?).ok()?;
+        self.mac(id, variant, expires.timestamp())
+            .verify_slice(&signature)
+            .ok()
+            .map(|_| expires)
+    }
+
+    fn url(&self, attachment: &Attachment, variant: AttachmentVariant, expires: Option<DateTime<Utc>>) -> String {
+        let path = format!("{}/{}/{}", DOWNLOAD_PATH, attachment.id, variant.as_str());
+
+        match expires {
+            Some(expires) => {
+                let expires = expires.timestamp();
+                let signature = hex::encode(self.mac(attachment.id, variant, expires).finalize().into_bytes());
+                format!("{}?expires={}&signature={}", path, expires, signature)
+            }
+            None => path,
+        }
+    }
+
+    pub fn to_response(&self, attachment: Attachment, signed: bool) -> AttachmentResponse {
+        let expires_at = signed.then(|| Utc::now().trunc_subsecs(0) + self.ttl);
+        let url = self.url(&attachment, AttachmentVariant::Original, expires_at);
+        let thumbnail_url = attachment
+            .thumbnail_key
+            .as_ref()
+            .map(|_| self.url(&attachment, AttachmentVariant::Thumbnail, expires_at));
+
+        AttachmentResponse {
+            id: attachment.id,
+            post_id: attachment.post_id,
+            user_id: attachment.user_id,
+            filename: attachment.filename,
+            content_type: attachment.content_type,
+            size_bytes: attachment.size_bytes,
+            sha256: attachment.sha256,
+            width: attachment.width,
+            height: attachment.height,
+            private: attachment.private,
+            url,
+            thumbnail_url,
+            expires_at,
+            created_at: attachment.created_at,
+        }
+    }
+}
\ No newline at end of file
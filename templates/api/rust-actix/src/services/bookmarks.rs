@@ -0,0 +1,225 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{Bookmark, BookmarkFolder, Cursor, ViewerBookmarks};
+
+pub struct BookmarkService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> BookmarkService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Bookmarks `post_id` for `user_id` in `folder_id`, or moves an
+    /// existing bookmark there. Returns the bookmark and whether it is new.
+    pub async fn save(
+        &self,
+        user_id: Uuid,
+        post_id: Uuid,
+        folder_id: Option<Uuid>,
+    ) -> Result<(Bookmark, bool), sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO bookmarks (user_id, post_id, folder_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, post_id) DO UPDATE SET folder_id = EXCLUDED.folder_id
+            RETURNING post_id, folder_id, created_at, xmax = 0 as "created!"
+            "#,
+            user_id,
+            post_id,
+            folder_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        let bookmark = Bookmark {
+            post_id: row.post_id,
+            folder_id: row.folder_id,
+            created_at: row.created_at,
+        };
+        Ok((bookmark, row.created))
+    }
+
+    /// Removes a bookmark; returns `false` if the post was not bookmarked.
+    pub async fn remove(&self, user_id: Uuid, post_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmarks WHERE user_id = $1 AND post_id = $2",
+            user_id,
+            post_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lists `user_id`'s bookmarks of posts they can still see, most
+    /// recently saved first, optionally only those in `folder_id`.
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        folder_id: Option<Uuid>,
+        cursor: Option<&Cursor>,
+        limit: i64,
+    ) -> Result<Vec<Bookmark>, sqlx::Error> {
+        let (after_created_at, after_post_id) = cursor
+            .and_then(|cursor| cursor.timestamp().map(|timestamp| (timestamp, cursor.id)))
+            .unzip();
+
+        sqlx::query_as!(
+            Bookmark,
+            r#"
+            SELECT b.post_id, b.folder_id, b.created_at
+            FROM bookmarks b
+            JOIN posts p ON p.id = b.post_id
+            WHERE b.user_id = $1
+              AND p.deleted_at IS NULL
+              AND ((p.status = 'published' AND p.hidden_at IS NULL) OR p.user_id = $1 OR EXISTS (
+                  SELECT 1 FROM post_collaborators pc WHERE pc.post_id = p.id AND pc.user_id = $1
+              ))
+              AND ($2::uuid IS NULL OR b.folder_id = $2)
+              AND ($3::timestamptz IS NULL OR (b.created_at, b.post_id) < ($3, $4::uuid))
+            ORDER BY b.created_at DESC, b.post_id DESC
+            LIMIT $5
+            "#,
+            user_id,
+            folder_id,
+            after_created_at,
+            after_post_id,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Which of `post_ids` the viewer bookmarked; `None` for anonymous
+    /// requests.
+    pub async fn for_viewer(
+        &self,
+        viewer: Option<Uuid>,
+        post_ids: &[Uuid],
+    ) -> Result<Option<ViewerBookmarks>, sqlx::Error> {
+        let viewer = match viewer {
+            Some(viewer) => viewer,
+            None => return Ok(None),
+        };
+
+        let bookmarked = sqlx::query_scalar!(
+            "SELECT post_id FROM bookmarks WHERE user_id = $1 AND post_id = ANY($2)",
+            viewer,
+            post_ids
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(Some(bookmarked.into_iter().collect()))
+    }
+
+    pub async fn folders(&self, user_id: Uuid) -> Result<Vec<BookmarkFolder>, sqlx::Error> {
+        sqlx::query_as!(
+            BookmarkFolder,
+            r#"
+            SELECT f.id, f.name, f.created_at, f.updated_at,
+                   (SELECT COUNT(*) FROM bookmarks b WHERE b.folder_id = f.id) as "bookmark_count!"
+            FROM bookmark_folders f
+            WHERE f.user_id = $1
+            ORDER BY f.name, f.id
+            "#,
+            user_id
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    pub async fn find_folder(&self, user_id: Uuid, folder_id: Uuid) -> Result<Option<BookmarkFolder>, sqlx::Error> {
+        sqlx::query_as!(
+            BookmarkFolder,
+            r#"
+            SELECT f.id, f.name, f.created_at, f.updated_at,
+                   (SELECT COUNT(*) FROM bookmarks b WHERE b.folder_id = f.id) as "bookmark_count!"
+            FROM bookmark_folders f
+            WHERE f.user_id = $1 AND f.id = $2
+            "#,
+            user_id,
+            folder_id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Whether `user_id` has another folder called `name`.
+    pub async fn folder_name_taken(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM bookmark_folders
+                WHERE user_id = $1 AND name = $2 AND ($3::uuid IS NULL OR id <> $3)
+            ) as "exists!"
+            "#,
+            user_id,
+            name,
+            except
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    pub async fn create_folder(&self, user_id: Uuid, name: &str) -> Result<BookmarkFolder, sqlx::Error> {
+        sqlx::query_as!(
+            BookmarkFolder,
+            r#"
+            INSERT INTO bookmark_folders (user_id, name)
+            VALUES ($1, $2)
+            RETURNING id, name, created_at, updated_at, 0::bigint as "bookmark_count!"
+            "#,
+            user_id,
+            name
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    pub async fn rename_folder(
+        &self,
+        user_id: Uuid,
+        folder_id: Uuid,
+        name: &str,
+    ) -> Result<Option<BookmarkFolder>, sqlx::Error> {
+        sqlx::query_as!(
+            BookmarkFolder,
+            r#"
+            UPDATE bookmark_folders f
+            SET name = $3, updated_at = NOW()
+            WHERE f.user_id = $1 AND f.id = $2
+            RETURNING f.id, f.name, f.created_at, f.updated_at,
+                      (SELECT COUNT(*) FROM bookmarks b WHERE b.folder_id = f.id) as "bookmark_count!"
+            "#,
+            user_id,
+            folder_id,
+            name
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Deletes a folder, leaving its bookmarks unfiled. Returns `false` if
+    /// `user_id` has no such folder.
+    pub async fn delete_folder(&self, user_id: Uuid, folder_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmark_folders WHERE user_id = $1 AND id = $2",
+            user_id,
+            folder_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
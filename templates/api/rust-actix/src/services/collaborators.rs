@@ -0,0 +1,87 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{Collaborator, CollaboratorRole};
+
+/// The role `user_id` was granted on `post_id`, if any.
+pub async fn collaborator_role(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<CollaboratorRole>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT role as "role: CollaboratorRole"
+        FROM post_collaborators
+        WHERE post_id = $1 AND user_id = $2
+        "#,
+        post_id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+pub struct CollaboratorService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> CollaboratorService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Shares `post_id` with `user_id`, or changes the role of an existing
+    /// grant. Returns the collaborator and whether the grant is new.
+    pub async fn upsert(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        role: CollaboratorRole,
+        invited_by: Uuid,
+    ) -> Result<(Collaborator, bool), sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            WITH grant_row AS (
+                INSERT INTO post_collaborators (post_id, user_id, role, invited_by)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (post_id, user_id)
+                DO UPDATE SET role = EXCLUDED.role, updated_at = NOW()
+                RETURNING user_id, role, created_at, updated_at
+            )
+            SELECT g.user_id, u.first_name, u.last_name, g.role as "role: CollaboratorRole",
+                   g.created_at, g.created_at = g.updated_at as "created!"
+            FROM grant_row g
+            JOIN users u ON u.id = g.user_id
+            "#,
+            post_id,
+            user_id,
+            role as CollaboratorRole,
+            invited_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        let collaborator = Collaborator {
+            user_id: row.user_id,
+            first_name: row.first_name,
+            last_name: row.last_name,
+            role: row.role,
+            created_at: row.created_at,
+        };
+        Ok((collaborator, row.created))
+    }
+
+    /// Revokes `user_id`'s access to `post_id`; returns `false` if they had none.
+    pub async fn remove(&self, post_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM post_collaborators WHERE post_id = $1 AND user_id = $2",
+            post_id,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
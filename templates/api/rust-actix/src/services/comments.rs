@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{CommentResponse, CommentWithUser, Cursor};
+
+pub struct CommentService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> CommentService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fetches up to `limit` top-level comments of a post, oldest first,
+    /// starting just after `cursor`.
+    pub async fn list_roots(
+        &self,
+        post_id: Uuid,
+        cursor: Option<&Cursor>,
+        limit: i64,
+    ) -> Result<Vec<CommentWithUser>, sqlx::Error> {
+        sqlx::query_as::<_, CommentWithUser>(
+            r#"
+            SELECT
+                c.id, c.post_id, c.user_id, c.parent_id, c.depth, c.content,
+                c.created_at, c.updated_at, c.deleted_at,
+                (SELECT COUNT(*) FROM comments r
+                 WHERE r.parent_id = c.id AND r.deleted_at IS NULL) AS reply_count,
+                u.first_name as user_first_name, u.last_name as user_last_name
+            FROM comments c
+            JOIN users u ON c.user_id = u.id
+            WHERE c.post_id = $1 AND c.parent_id IS NULL
+              AND ($2::timestamptz IS NULL OR (c.created_at, c.id) > ($2, $3::uuid))
+            ORDER BY c.created_at, c.id
+            LIMIT $4
+            "#,
+        )
+        .bind(post_id)
+        .bind(cursor.and_then(Cursor::timestamp))
+        .bind(cursor.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Fetches every reply below `root_ids` down to `max_depth`, oldest first.
+    pub async fn list_replies(
+        &self,
+        root_ids: &[Uuid],
+        max_depth: i32,
+    ) -> Result<Vec<CommentWithUser>, sqlx::Error> {
+        sqlx::query_as::<_, CommentWithUser>(
+            r#"
+            WITH RECURSIVE thread AS (
+                SELECT c.* FROM comments c
+                WHERE c.parent_id = ANY($1) AND c.depth <= $2
+                UNION ALL
+                SELECT c.* FROM comments c
+                JOIN thread t ON c.parent_id = t.id
+                WHERE c.depth <= $2
+            )
+            SELECT
+                c.id, c.post_id, c.user_id, c.parent_id, c.depth, c.content,
+                c.created_at, c.updated_at, c.deleted_at,
+                (SELECT COUNT(*) FROM comments r
+                 WHERE r.parent_id = c.id AND r.deleted_at IS NULL) AS reply_count,
+                u.first_name as user_first_name, u.last_name as user_last_name
+            FROM thread c
+            JOIN users u ON c.user_id = u.id
+            ORDER BY c.created_at, c.id
+            "#,
+        )
+        .bind(root_ids)
+        .bind(max_depth)
+        .fetch_all(self.pool)
+        .await
+    }
+}
+
+/// Assembles top-level comments and their replies into a tree.
+///
+/// Deleted comments stay in the tree as placeholders while they still have
+/// live replies, so that threads remain readable, and are dropped otherwise.
+/// Replies below `max_depth` were not fetched, so at that level `reply_count`
+/// decides instead.
+pub fn build_comment_tree(
+    roots: Vec<CommentWithUser>,
+    replies: Vec<CommentWithUser>,
+    max_depth: i32,
+) -> Vec<CommentResponse> {
+    let mut children: HashMap<Uuid, Vec<CommentResponse>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.parent_id {
+            children.entry(parent_id).or_default().push(reply.into());
+        }
+    }
+
+    roots
+        .into_iter()
+        .filter_map(|root| attach_replies(root.into(), &mut children, max_depth))
+        .collect()
+}
+
+fn attach_replies(
+    mut node: CommentResponse,
+    children: &mut HashMap<Uuid, Vec<CommentResponse>>,
+    max_depth: i32,
+) -> Option<CommentResponse> {
+    node.replies = children
+        .remove(&node.id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|child| attach_replies(child, children, max_depth))
+        .collect();
+
+    let has_replies = if node.depth < max_depth {
+        !node.replies.is_empty()
+    } else {
+        node.reply_count > 0
+    };
+
+    if node.deleted && !has_replies {
+        None
+    } else {
+        Some(node)
+    }
+}
\ No newline at end of file
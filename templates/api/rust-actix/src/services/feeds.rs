@@ -0,0 +1,189 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::models::{FeedFormat, JsonFeed, JsonFeedAuthor, JsonFeedItem, PostWithUser};
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// Feed-level metadata; all URLs are absolute.
+pub struct FeedInfo {
+    pub title: String,
+    pub description: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    /// Post links are this followed by `/{slug}`.
+    pub post_url_base: String,
+}
+
+impl FeedInfo {
+    fn post_url(&self, post: &PostWithUser) -> String {
+        format!("{}/{}", self.post_url_base, post.slug)
+    }
+}
+
+/// Renders published posts, newest first, as a feed document.
+pub fn render_feed(format: FeedFormat, info: &FeedInfo, posts: &[PostWithUser]) -> String {
+    match format {
+        FeedFormat::Rss => render_rss(info, posts),
+        FeedFormat::Atom => render_atom(info, posts),
+        FeedFormat::Json => render_json_feed(info, posts),
+    }
+}
+
+/// When the feed last changed. Empty feeds report the Unix epoch so their
+/// body, and thus their ETag, stays stable.
+pub fn feed_updated(posts: &[PostWithUser]) -> DateTime<Utc> {
+    posts
+        .iter()
+        .map(|post| post.updated_at)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+fn render_rss(info: &FeedInfo, posts: &[PostWithUser]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>"#);
+    let _ = write!(
+        xml,
+        r#"<title>{}</title><link>{}</link><description>{}</description><atom:link href="{}" rel="self" type="application/rss+xml"/><lastBuildDate>{}</lastBuildDate>"#,
+        escape_xml(&info.title),
+        escape_xml(&info.home_page_url),
+        escape_xml(&info.description),
+        escape_xml(&info.feed_url),
+        feed_updated(posts).to_rfc2822()
+    );
+
+    for post in posts {
+        let _ = write!(
+            xml,
+            r#"<item><title>{}</title><link>{}</link><guid isPermaLink="false">urn:uuid:{}</guid><pubDate>{}</pubDate><dc:creator>{}</dc:creator>"#,
+            escape_xml(&post.title),
+            escape_xml(&info.post_url(post)),
+            post.id,
+            published_at(post).to_rfc2822(),
+            escape_xml(&author_name(post))
+        );
+        for tag in &post.tags {
+            let _ = write!(xml, "<category>{}</category>", escape_xml(tag));
+        }
+        let _ = write!(xml, "<description>{}</description></item>", escape_xml(&post.content_html));
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn render_atom(info: &FeedInfo, posts: &[PostWithUser]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = write!(
+        xml,
+        r#"<id>{}</id><title>{}</title><subtitle>{}</subtitle><updated>{}</updated><link rel="self" type="application/atom+xml" href="{}"/><link rel="alternate" href="{}"/>"#,
+        escape_xml(&info.feed_url),
+        escape_xml(&info.title),
+        escape_xml(&info.description),
+        atom_date(feed_updated(posts)),
+        escape_xml(&info.feed_url),
+        escape_xml(&info.home_page_url)
+    );
+
+    for post in posts {
+        let _ = write!(
+            xml,
+            r#"<entry><id>urn:uuid:{}</id><title>{}</title><link rel="alternate" href="{}"/><published>{}</published><updated>{}</updated><author><name>{}</name></author>"#,
+            post.id,
+            escape_xml(&post.title),
+            escape_xml(&info.post_url(post)),
+            atom_date(published_at(post)),
+            atom_date(post.updated_at),
+            escape_xml(&author_name(post))
+        );
+        for tag in &post.tags {
+            let _ = write!(xml, r#"<category term="{}"/>"#, escape_xml(tag));
+        }
+        let _ = write!(
+            xml,
+            r#"<summary>{}</summary><content type="html">{}</content></entry>"#,
+            escape_xml(&post.excerpt),
+            escape_xml(&post.content_html)
+        );
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn render_json_feed(info: &FeedInfo, posts: &[PostWithUser]) -> String {
+    let feed = JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: info.title.clone(),
+        home_page_url: info.home_page_url.clone(),
+        feed_url: info.feed_url.clone(),
+        items: posts
+            .iter()
+            .map(|post| JsonFeedItem {
+                id: post.id.to_string(),
+                url: info.post_url(post),
+                title: post.title.clone(),
+                content_html: post.content_html.clone(),
+                summary: post.excerpt.clone(),
+                date_published: published_at(post),
+                date_modified: post.updated_at,
+                authors: vec![JsonFeedAuthor { name: author_name(post) }],
+                tags: post.tags.clone(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&feed).unwrap_or_default()
+}
+
+fn published_at(post: &PostWithUser) -> DateTime<Utc> {
+    post.publish_at.unwrap_or(post.created_at)
+}
+
+fn author_name(post: &PostWithUser) -> String {
+    format!("{} {}", post.user_first_name, post.user_last_name)
+}
+
+fn atom_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Escapes text for XML element content and attribute values, dropping
+/// control characters XML 1.0 does not allow at all.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_characters_are_escaped() {
+        assert_eq!(
+            escape_xml(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn disallowed_control_characters_are_dropped() {
+        assert_eq!(escape_xml("tab\tline\nbell\u{7}null\u{0}"), "tab\tline\nbellnull");
+    }
+}
\ No newline at end of file
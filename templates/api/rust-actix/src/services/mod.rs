@@ -1,3 +1,37 @@
+pub mod attachments;
 pub mod auth;
+pub mod bookmarks;
+pub mod collaborators;
+pub mod comments;
+pub mod feeds;
+pub mod moderation;
+pub mod posts;
+pub mod reactions;
+pub mod rendering;
+pub mod revisions;
+pub mod scheduler;
+pub mod slugs;
+pub mod tags;
+pub mod transfer;
+pub mod translations;
+pub mod uploads;
+pub mod views;
 
-pub use auth::*;
\ No newline at end of file
+pub use attachments::*;
+pub use auth::*;
+pub use bookmarks::*;
+pub use collaborators::*;
+pub use comments::*;
+pub use feeds::*;
+pub use moderation::*;
+pub use posts::*;
+pub use reactions::*;
+pub use rendering::*;
+pub use revisions::*;
+pub use scheduler::*;
+pub use slugs::*;
+pub use tags::*;
+pub use transfer::*;
+pub use translations::*;
+pub use uploads::*;
+pub use views::*;
\ No newline at end of file
@@ -0,0 +1,324 @@
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::models::{
+    Cursor, ModerationAction, ModerationActionKind, QueuedReport, Report, ReportQueueQuery,
+    ReportReason, ReportStatus,
+};
+
+const QUEUED_REPORT_SELECT: &str = r#"
+    SELECT
+        r.id, r.post_id, r.reporter_id, r.reason, r.details, r.status, r.resolved_by,
+        r.resolved_at, r.created_at,
+        p.title as post_title, p.user_id as post_user_id, p.hidden_at as post_hidden_at,
+        (SELECT COUNT(*) FROM reports o WHERE o.post_id = r.post_id AND o.status = 'open') as post_open_reports
+    FROM reports r
+    JOIN posts p ON p.id = r.post_id
+"#;
+
+/// Outcome of a new report.
+pub enum ReportOutcome {
+    Created {
+        report: Report,
+        /// Whether this report took the post over the hide threshold.
+        hidden: bool,
+    },
+    /// The reporter already has an open report on the post.
+    Duplicate,
+}
+
+pub struct ModerationService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> ModerationService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn is_moderator(&self, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let is_moderator = sqlx::query_scalar!(
+            "SELECT is_moderator FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(is_moderator.unwrap_or(false))
+    }
+
+    /// Files a report and hides the post once it has `hide_threshold` open
+    /// reports (never, if the threshold is zero).
+    pub async fn report(
+        &self,
+        post_id: Uuid,
+        reporter_id: Uuid,
+        reason: ReportReason,
+        details: Option<&str>,
+        hide_threshold: i64,
+    ) -> Result<ReportOutcome, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let report = sqlx::query_as!(
+            Report,
+            r#"
+            INSERT INTO reports (post_id, reporter_id, reason, details)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (post_id, reporter_id) WHERE status = 'open' DO NOTHING
+            RETURNING id, post_id, reporter_id, reason as "reason: ReportReason", details,
+                      status as "status: ReportStatus", resolved_by, resolved_at, created_at
+            "#,
+            post_id,
+            reporter_id,
+            reason as ReportReason,
+            details
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(report) = report else {
+            return Ok(ReportOutcome::Duplicate);
+        };
+
+        let mut hidden = false;
+        if hide_threshold > 0 {
+            // Concurrent reports on the post count one after the other, so
+            // that the one reaching the threshold sees all the others
+            sqlx::query!("SELECT id FROM posts WHERE id = $1 FOR UPDATE", post_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            let open_reports = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) as "count!" FROM reports WHERE post_id = $1 AND status = 'open'"#,
+                post_id
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if open_reports >= hide_threshold {
+                let result = sqlx::query!(
+                    r#"
+                    UPDATE posts SET hidden_at = NOW(), hidden_by = NULL, updated_at = NOW()
+                    WHERE id = $1 AND hidden_at IS NULL
+                    "#,
+                    post_id
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                if result.rows_affected() > 0 {
+                    hidden = true;
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO moderation_actions (post_id, report_id, target_user_id, action, note)
+                        SELECT id, $2, user_id, 'hide_post', $3 FROM posts WHERE id = $1
+                        "#,
+                        post_id,
+                        report.id,
+                        format!("Hidden automatically after {} open reports", open_reports)
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(ReportOutcome::Created { report, hidden })
+    }
+
+    /// Reports with the given status, oldest first.
+    pub async fn queue(
+        &self,
+        query: &ReportQueueQuery,
+        cursor: Option<&Cursor>,
+        limit: i64,
+    ) -> Result<Vec<QueuedReport>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(QUEUED_REPORT_SELECT);
+        builder.push(" WHERE r.status = ").push_bind(query.status);
+
+        if let Some(reason) = query.reason {
+            builder.push(" AND r.reason = ").push_bind(reason);
+        }
+        if let Some(post_id) = query.post_id {
+            builder.push(" AND r.post_id = ").push_bind(post_id);
+        }
+        if let Some(cursor) = cursor {
+            builder
+                .push(" AND (r.created_at, r.id) > (")
+                .push_bind(cursor.timestamp())
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        builder
+            .push(" ORDER BY r.created_at ASC, r.id ASC LIMIT ")
+            .push_bind(limit);
+
+        builder
+            .build_query_as::<QueuedReport>()
+            .fetch_all(self.pool)
+            .await
+    }
+
+    pub async fn find_report(&self, id: Uuid) -> Result<Option<Report>, sqlx::Error> {
+        sqlx::query_as!(
+            Report,
+            r#"
+            SELECT id, post_id, reporter_id, reason as "reason: ReportReason", details,
+                   status as "status: ReportStatus", resolved_by, resolved_at, created_at
+            FROM reports
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Applies a moderator's decision on `report` and resolves every open
+    /// report of the same post, recording the action in the audit log.
+    pub async fn act(
+        &self,
+        report: &Report,
+        moderator_id: Uuid,
+        action: ModerationActionKind,
+        note: Option<&str>,
+    ) -> Result<Option<ModerationAction>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let author_id = sqlx::query_scalar!(
+            "SELECT user_id FROM posts WHERE id = $1 FOR UPDATE",
+            report.post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Reports are resolved under the post lock; another moderator may
+        // have got there first
+        let status = sqlx::query_scalar!(
+            r#"SELECT status as "status: ReportStatus" FROM reports WHERE id = $1"#,
+            report.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if status != ReportStatus::Open {
+            return Ok(None);
+        }
+
+        let resolution = match action {
+            ModerationActionKind::Dismiss => {
+                // Undo automatic hiding, but not a moderator's decision
+                sqlx::query!(
+                    r#"
+                    UPDATE posts SET hidden_at = NULL, updated_at = NOW()
+                    WHERE id = $1 AND hidden_at IS NOT NULL AND hidden_by IS NULL
+                    "#,
+                    report.post_id
+                )
+                .execute(&mut *tx)
+                .await?;
+                ReportStatus::Dismissed
+            }
+            ModerationActionKind::HidePost => {
+                sqlx::query!(
+                    r#"
+                    UPDATE posts
+                    SET hidden_at = COALESCE(hidden_at, NOW()), hidden_by = $2, updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                    report.post_id,
+                    moderator_id
+                )
+                .execute(&mut *tx)
+                .await?;
+                ReportStatus::Actioned
+            }
+            ModerationActionKind::SuspendAuthor => {
+                sqlx::query!(
+                    "UPDATE users SET suspended_at = COALESCE(suspended_at, NOW()) WHERE id = $1",
+                    author_id
+                )
+                .execute(&mut *tx)
+                .await?;
+                ReportStatus::Actioned
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE reports SET status = $2, resolved_by = $3, resolved_at = NOW()
+            WHERE post_id = $1 AND status = 'open'
+            "#,
+            report.post_id,
+            resolution as ReportStatus,
+            moderator_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let recorded = sqlx::query_as!(
+            ModerationAction,
+            r#"
+            INSERT INTO moderation_actions (post_id, report_id, target_user_id, moderator_id, action, note)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, post_id, report_id, target_user_id, moderator_id,
+                      action as "action: ModerationActionKind", note, created_at
+            "#,
+            report.post_id,
+            report.id,
+            author_id,
+            moderator_id,
+            action as ModerationActionKind,
+            note
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(recorded))
+    }
+
+    /// The moderation audit log, newest first.
+    pub async fn actions(
+        &self,
+        post_id: Option<Uuid>,
+        moderator_id: Option<Uuid>,
+        cursor: Option<&Cursor>,
+        limit: i64,
+    ) -> Result<Vec<ModerationAction>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT id, post_id, report_id, target_user_id, moderator_id, action, note, created_at
+            FROM moderation_actions
+            WHERE TRUE
+            "#,
+        );
+
+        if let Some(post_id) = post_id {
+            builder.push(" AND post_id = ").push_bind(post_id);
+        }
+        if let Some(moderator_id) = moderator_id {
+            builder.push(" AND moderator_id = ").push_bind(moderator_id);
+        }
+        if let Some(cursor) = cursor {
+            builder
+                .push(" AND (created_at, id) < (")
+                .push_bind(cursor.timestamp())
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        builder
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit);
+
+        builder
+            .build_query_as::<ModerationAction>()
+            .fetch_all(self.pool)
+            .await
+    }
+}
\ No newline at end of file
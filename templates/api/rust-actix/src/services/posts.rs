@@ -0,0 +1,489 @@
+use sqlx::{types::Json, PgPool, Postgres, QueryBuilder};
+
+use uuid::Uuid;
+
+use crate::models::{
+    Collaborator, CollaboratorRole, ContentFormat, Cursor, FieldSelection, PostListQuery, PostSearchRow,
+    PostSortField, PostStatus, PostWithUser, ReactionCounts, TagMatch,
+};
+
+use super::highlight_html;
+
+/// Parts of a [`PostWithUser`] query that field selections can leave out.
+/// The other columns are small and always selected, since visibility
+/// checks, cursors and cache validators rely on them.
+#[derive(Debug, Clone, Copy)]
+pub struct PostColumns {
+    pub content: bool,
+    pub content_html: bool,
+    pub excerpt: bool,
+    pub tags: bool,
+    pub collaborators: bool,
+    pub reaction_counts: bool,
+    /// Join the author for the `user` expansion.
+    pub user: bool,
+}
+
+impl PostColumns {
+    pub const ALL: Self = Self {
+        content: true,
+        content_html: true,
+        excerpt: true,
+        tags: true,
+        collaborators: true,
+        reaction_counts: true,
+        user: true,
+    };
+
+    pub fn for_selection(selection: &FieldSelection) -> Self {
+        Self {
+            content: selection.includes("content"),
+            content_html: selection.includes("content_html"),
+            excerpt: selection.includes("excerpt"),
+            tags: selection.includes("tags"),
+            collaborators: selection.includes("collaborators"),
+            reaction_counts: selection.includes("reaction_counts"),
+            user: selection.expands("user"),
+        }
+    }
+}
+
+/// Select list of a [`PostWithUser`] query over `posts p`, and `users u`
+/// if `columns.user` is set. Left-out columns get their `#[sqlx(default)]`.
+fn post_select_list(columns: PostColumns) -> String {
+    let mut list = String::from(
+        "p.id, p.title, p.slug, p.content_format, p.reading_time_minutes, p.locale, p.user_id, p.status, \
+         p.publish_at, p.comment_count, p.created_at, p.updated_at, p.deleted_at, p.hidden_at,
+         ARRAY(
+             SELECT pt.locale FROM post_translations pt WHERE pt.post_id = p.id ORDER BY pt.locale
+         ) as translations",
+    );
+    if columns.content {
+        list.push_str(", p.content");
+    }
+    if columns.content_html {
+        list.push_str(", p.content_html");
+    }
+    if columns.excerpt {
+        list.push_str(", p.excerpt");
+    }
+    if columns.reaction_counts {
+        list.push_str(", p.reaction_counts");
+    }
+    if columns.tags {
+        list.push_str(
+            r#",
+            ARRAY(
+                SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE pt.post_id = p.id ORDER BY t.name
+            ) as tags"#,
+        );
+    }
+    if columns.collaborators {
+        list.push_str(
+            r#",
+            COALESCE((
+                SELECT json_agg(json_build_object(
+                    'user_id', cu.id, 'first_name', cu.first_name, 'last_name', cu.last_name,
+                    'role', pc.role, 'created_at', pc.created_at
+                ) ORDER BY pc.created_at, pc.user_id)
+                FROM post_collaborators pc JOIN users cu ON cu.id = pc.user_id
+                WHERE pc.post_id = p.id
+            ), '[]') as collaborators"#,
+        );
+    }
+    if columns.user {
+        list.push_str(
+            r#",
+            u.email as user_email, u.first_name as user_first_name,
+            u.last_name as user_last_name, u.created_at as user_created_at,
+            u.updated_at as user_updated_at"#,
+        );
+    }
+    list
+}
+
+fn post_select(columns: PostColumns) -> String {
+    let mut select = format!("SELECT {} FROM posts p", post_select_list(columns));
+    if columns.user {
+        select.push_str(" JOIN users u ON p.user_id = u.id");
+    }
+    select
+}
+
+pub struct PostService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> PostService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_with_user(&self, id: Uuid) -> Result<Option<PostWithUser>, sqlx::Error> {
+        sqlx::query_as!(
+            PostWithUser,
+            r#"
+            SELECT
+                p.id, p.title, p.slug, p.content,
+                p.content_format as "content_format: ContentFormat", p.content_html, p.excerpt,
+                p.reading_time_minutes, p.locale, p.user_id, p.status as "status: PostStatus",
+                p.publish_at, p.comment_count, p.reaction_counts as "reaction_counts: Json<ReactionCounts>",
+                p.created_at, p.updated_at, p.deleted_at, p.hidden_at,
+                ARRAY(
+                    SELECT pt.locale FROM post_translations pt WHERE pt.post_id = p.id ORDER BY pt.locale
+                ) as "translations!",
+                ARRAY(
+                    SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                    WHERE pt.post_id = p.id ORDER BY t.name
+                ) as "tags!",
+                COALESCE((
+                    SELECT json_agg(json_build_object(
+                        'user_id', cu.id, 'first_name', cu.first_name, 'last_name', cu.last_name,
+                        'role', pc.role, 'created_at', pc.created_at
+                    ) ORDER BY pc.created_at, pc.user_id)
+                    FROM post_collaborators pc JOIN users cu ON cu.id = pc.user_id
+                    WHERE pc.post_id = p.id
+                ), '[]') as "collaborators!: Json<Vec<Collaborator>>",
+                u.email as user_email, u.first_name as user_first_name,
+                u.last_name as user_last_name, u.created_at as user_created_at,
+                u.updated_at as user_updated_at
+            FROM posts p
+            JOIN users u ON p.user_id = u.id
+            WHERE p.id = $1 AND p.deleted_at IS NULL
+            "#,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Fetches the posts among `ids` that are not in the trash, in no
+    /// particular order.
+    pub async fn find_many_with_user(&self, ids: &[Uuid]) -> Result<Vec<PostWithUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(post_select(PostColumns::ALL));
+        builder
+            .push(" WHERE p.deleted_at IS NULL AND p.id = ANY(")
+            .push_bind(ids)
+            .push(")");
+
+        builder
+            .build_query_as::<PostWithUser>()
+            .fetch_all(self.pool)
+            .await
+    }
+
+    /// Fetches a post that is not in the trash with only `columns` of the
+    /// optional ones, like [`PostService::find_with_user`] otherwise.
+    pub async fn find_selected(&self, id: Uuid, columns: PostColumns) -> Result<Option<PostWithUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(post_select(columns));
+        builder
+            .push(" WHERE p.id = ")
+            .push_bind(id)
+            .push(" AND p.deleted_at IS NULL");
+
+        builder
+            .build_query_as::<PostWithUser>()
+            .fetch_optional(self.pool)
+            .await
+    }
+
+    /// Fetches up to `limit` posts matching `query`, ordered by the requested
+    /// sort and starting just after `cursor`.
+    ///
+    /// Unpublished posts are only included for their author and
+    /// collaborators (`viewer`).
+    pub async fn list(
+        &self,
+        query: &PostListQuery,
+        viewer: Option<Uuid>,
+        cursor: Option<&Cursor>,
+        limit: i64,
+        columns: PostColumns,
+    ) -> Result<Vec<PostWithUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(post_select(columns));
+        builder
+            .push(" WHERE p.deleted_at IS NULL AND ((p.status = 'published' AND p.hidden_at IS NULL) OR p.user_id = ")
+            .push_bind(viewer)
+            .push(" OR EXISTS (SELECT 1 FROM post_collaborators pc WHERE pc.post_id = p.id AND pc.user_id = ")
+            .push_bind(viewer)
+            .push("))");
+
+        if let Some(author_id) = query.author_id {
+            builder.push(" AND p.user_id = ").push_bind(author_id);
+        }
+        if let Some(created_after) = query.created_after {
+            builder.push(" AND p.created_at >= ").push_bind(created_after);
+        }
+        if let Some(created_before) = query.created_before {
+            builder.push(" AND p.created_at < ").push_bind(created_before);
+        }
+        if let Some(updated_after) = query.updated_after {
+            builder.push(" AND p.updated_at >= ").push_bind(updated_after);
+        }
+        if let Some(updated_before) = query.updated_before {
+            builder.push(" AND p.updated_at < ").push_bind(updated_before);
+        }
+        if let Some(status) = query.status {
+            builder.push(" AND p.status = ").push_bind(status);
+        }
+        let tags = query.tags();
+        if !tags.is_empty() {
+            // Tag names resolve to ids once, so post_tags is probed through its
+            // tag_id index rather than aggregated for every candidate post.
+            let count = tags.len() as i64;
+            let tag_ids = "(ARRAY(SELECT t.id FROM tags t WHERE t.name = ANY(";
+            match query.tag_match {
+                TagMatch::Any => {
+                    builder
+                        .push(" AND EXISTS (SELECT 1 FROM post_tags pt WHERE pt.post_id = p.id AND pt.tag_id = ANY")
+                        .push(tag_ids)
+                        .push_bind(tags)
+                        .push("))))");
+                }
+                // Unknown names never match, so counting against the requested
+                // names leaves such a filter empty.
+                TagMatch::All => {
+                    builder
+                        .push(" AND p.id IN (SELECT pt.post_id FROM post_tags pt WHERE pt.tag_id = ANY")
+                        .push(tag_ids)
+                        .push_bind(tags)
+                        .push("))) GROUP BY pt.post_id HAVING COUNT(*) = ")
+                        .push_bind(count)
+                        .push(")");
+                }
+            }
+        }
+        if let Some(title) = &query.title {
+            builder
+                .push(" AND p.title ILIKE ")
+                .push_bind(format!("%{}%", escape_like(title)));
+        }
+
+        let column = query.sort.as_str();
+
+        if let Some(cursor) = cursor {
+            builder.push(format!(
+                " AND (p.{}, p.id) {} (",
+                column,
+                query.direction.keyset_operator()
+            ));
+            match query.sort {
+                PostSortField::Title => builder.push_bind(cursor.key.clone()),
+                PostSortField::CreatedAt | PostSortField::UpdatedAt => {
+                    builder.push_bind(cursor.timestamp())
+                }
+            };
+            builder.push(", ").push_bind(cursor.id).push(")");
+        }
+
+        let direction = query.direction.as_sql();
+        builder.push(format!(
+            " ORDER BY p.{} {}, p.id {} LIMIT ",
+            column, direction, direction
+        ));
+        builder.push_bind(limit);
+
+        builder
+            .build_query_as::<PostWithUser>()
+            .fetch_all(self.pool)
+            .await
+    }
+
+    /// Whether `posts.search_vector` is built with the text search
+    /// configuration `language`. Searching with another one would neither
+    /// use the index nor match the stored lexemes.
+    pub async fn search_vector_uses(&self, language: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT position(quote_literal($1::regconfig::text) || '::regconfig' IN pg_get_expr(d.adbin, d.adrelid)) > 0
+                AS "uses!"
+            FROM pg_attrdef d
+            JOIN pg_attribute a ON a.attrelid = d.adrelid AND a.attnum = d.adnum
+            WHERE d.adrelid = 'posts'::regclass AND a.attname = 'search_vector'
+            "#,
+            language as _
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    /// Ranks posts matching the `websearch_to_tsquery` expression `terms`,
+    /// resuming after the `(rank, id)` position of `after`.
+    ///
+    /// Headlines are only generated for the returned page, since
+    /// `ts_headline` re-parses the whole document.
+    pub async fn search(
+        &self,
+        language: &str,
+        terms: &str,
+        viewer: Option<Uuid>,
+        after: Option<(f32, Uuid)>,
+        limit: i64,
+        columns: PostColumns,
+    ) -> Result<Vec<PostSearchRow>, sqlx::Error> {
+        let sql = format!(
+            r#"
+            WITH search AS (
+                SELECT websearch_to_tsquery($1::regconfig, $2) AS query
+            ),
+            page AS (
+                SELECT p.id, ts_rank(p.search_vector, search.query) AS rank
+                FROM posts p, search
+                WHERE p.search_vector @@ search.query
+                  AND p.deleted_at IS NULL
+                  AND ((p.status = 'published' AND p.hidden_at IS NULL) OR p.user_id = $6 OR EXISTS (
+                      SELECT 1 FROM post_collaborators pc WHERE pc.post_id = p.id AND pc.user_id = $6
+                  ))
+                  AND ($3::real IS NULL OR (ts_rank(p.search_vector, search.query), p.id) < ($3, $4::uuid))
+                ORDER BY rank DESC, p.id DESC
+                LIMIT $5
+            )
+            SELECT
+                {},
+                page.rank,
+                ts_headline($1::regconfig, translate(p.title, chr(1) || chr(2), ''), search.query,
+                    'StartSel=' || chr(1) || ', StopSel=' || chr(2) || ', HighlightAll=true') AS title_highlight,
+                ts_headline($1::regconfig, translate(p.content, chr(1) || chr(2), ''), search.query,
+                    'StartSel=' || chr(1) || ', StopSel=' || chr(2) || ', MaxFragments=2, MinWords=10, MaxWords=30')
+                    AS content_snippet
+            FROM page
+            JOIN posts p ON p.id = page.id
+            {}
+            CROSS JOIN search
+            ORDER BY page.rank DESC, p.id DESC
+            "#,
+            post_select_list(columns),
+            if columns.user { "JOIN users u ON p.user_id = u.id" } else { "" },
+        );
+
+        let rows = sqlx::query_as::<_, PostSearchRow>(&sql)
+            .bind(language)
+            .bind(terms)
+            .bind(after.map(|(rank, _)| rank))
+            .bind(after.map(|(_, id)| id))
+            .bind(limit)
+            .bind(viewer)
+            .fetch_all(self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|mut row| {
+                row.title_highlight = highlight_html(&row.title_highlight);
+                row.content_snippet = highlight_html(&row.content_snippet);
+                row
+            })
+            .collect())
+    }
+
+    /// Lists the posts shared with `user_id`, optionally only those with
+    /// `role`, most recently updated first.
+    pub async fn list_shared(
+        &self,
+        user_id: Uuid,
+        role: Option<CollaboratorRole>,
+        cursor: Option<&Cursor>,
+        limit: i64,
+        columns: PostColumns,
+    ) -> Result<Vec<PostWithUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(post_select(columns));
+        builder
+            .push(" WHERE p.deleted_at IS NULL AND EXISTS (SELECT 1 FROM post_collaborators pc WHERE pc.post_id = p.id AND pc.user_id = ")
+            .push_bind(user_id);
+        if let Some(role) = role {
+            builder.push(" AND pc.role = ").push_bind(role);
+        }
+        builder.push(")");
+
+        if let Some(cursor) = cursor {
+            builder
+                .push(" AND (p.updated_at, p.id) < (")
+                .push_bind(cursor.timestamp())
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        builder
+            .push(" ORDER BY p.updated_at DESC, p.id DESC LIMIT ")
+            .push_bind(limit);
+
+        builder
+            .build_query_as::<PostWithUser>()
+            .fetch_all(self.pool)
+            .await
+    }
+
+    /// Lists the trashed posts of `user_id`, most recently deleted first.
+    pub async fn list_trash(
+        &self,
+        user_id: Uuid,
+        cursor: Option<&Cursor>,
+        limit: i64,
+        columns: PostColumns,
+    ) -> Result<Vec<PostWithUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(post_select(columns));
+        builder
+            .push(" WHERE p.deleted_at IS NOT NULL AND p.user_id = ")
+            .push_bind(user_id);
+
+        if let Some(cursor) = cursor {
+            builder
+                .push(" AND (p.deleted_at, p.id) < (")
+                .push_bind(cursor.timestamp())
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        builder
+            .push(" ORDER BY p.deleted_at DESC, p.id DESC LIMIT ")
+            .push_bind(limit);
+
+        builder
+            .build_query_as::<PostWithUser>()
+            .fetch_all(self.pool)
+            .await
+    }
+
+    /// Permanently removes posts that have been in the trash longer than
+    /// `retention_days`.
+    pub async fn purge_trash(&self, retention_days: i32) -> Result<u64, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM posts
+            WHERE deleted_at < NOW() - make_interval(days => $1)
+            "#,
+            retention_days
+        )
+        .execute(self.pool)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    /// Publishes every scheduled post whose `publish_at` has passed.
+    ///
+    /// State lives entirely in the database, so posts that came due while
+    /// the server was down are picked up on the next run.
+    pub async fn publish_due(&self) -> Result<u64, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE posts
+            SET status = 'published', updated_at = NOW()
+            WHERE status = 'scheduled' AND publish_at <= NOW() AND deleted_at IS NULL
+            "#
+        )
+        .execute(self.pool)
+        .await
+        .map(|result| result.rows_affected())
+    }
+}
+
+/// Escapes `LIKE` wildcards so user input is matched literally.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
\ No newline at end of file
@@ -0,0 +1,166 @@
+use sqlx::{types::Json, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{ReactionCounts, ReactionSummary, ViewerReactions};
+
+pub struct ReactionService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> ReactionService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Adds `user_id`'s `kind` reaction to a post. Reacting twice with the
+    /// same kind leaves the counts unchanged.
+    pub async fn react(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<ReactionSummary, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO post_reactions (post_id, user_id, kind)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+            post_id,
+            user_id,
+            kind
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted > 0 {
+            sqlx::query!(
+                r#"
+                UPDATE posts
+                SET reaction_counts = jsonb_set(
+                    reaction_counts, ARRAY[$2::text],
+                    to_jsonb(COALESCE((reaction_counts->>$2::text)::bigint, 0) + 1)
+                )
+                WHERE id = $1
+                "#,
+                post_id,
+                kind
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let summary = summarize(&mut tx, post_id, user_id).await?;
+        tx.commit().await?;
+
+        Ok(summary)
+    }
+
+    /// Removes `user_id`'s `kind` reaction from a post, if there is one.
+    pub async fn unreact(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<ReactionSummary, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM post_reactions WHERE post_id = $1 AND user_id = $2 AND kind = $3",
+            post_id,
+            user_id,
+            kind
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if deleted > 0 {
+            // Drop the key with its last reaction so counts only list kinds in use
+            sqlx::query!(
+                r#"
+                UPDATE posts
+                SET reaction_counts = CASE
+                    WHEN (reaction_counts->>$2::text)::bigint > 1 THEN jsonb_set(
+                        reaction_counts, ARRAY[$2::text],
+                        to_jsonb((reaction_counts->>$2::text)::bigint - 1)
+                    )
+                    ELSE reaction_counts - $2::text
+                END
+                WHERE id = $1
+                "#,
+                post_id,
+                kind
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let summary = summarize(&mut tx, post_id, user_id).await?;
+        tx.commit().await?;
+
+        Ok(summary)
+    }
+
+    /// Looks up which kinds `viewer` reacted with on each of `post_ids`.
+    ///
+    /// Returns `None` for anonymous requests without touching the database.
+    pub async fn for_viewer(
+        &self,
+        viewer: Option<Uuid>,
+        post_ids: &[Uuid],
+    ) -> Result<Option<ViewerReactions>, sqlx::Error> {
+        let viewer = match viewer {
+            Some(viewer) => viewer,
+            None => return Ok(None),
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT post_id, array_agg(kind ORDER BY kind) as "kinds!"
+            FROM post_reactions
+            WHERE user_id = $1 AND post_id = ANY($2)
+            GROUP BY post_id
+            "#,
+            viewer,
+            post_ids
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(Some(rows.into_iter().map(|row| (row.post_id, row.kinds)).collect()))
+    }
+}
+
+async fn summarize(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    user_id: Uuid,
+) -> Result<ReactionSummary, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            p.reaction_counts as "reaction_counts: Json<ReactionCounts>",
+            ARRAY(
+                SELECT kind::text FROM post_reactions r
+                WHERE r.post_id = p.id AND r.user_id = $2
+                ORDER BY kind
+            ) as "viewer_reactions!"
+        FROM posts p
+        WHERE p.id = $1
+        "#,
+        post_id,
+        user_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(ReactionSummary {
+        post_id,
+        reaction_counts: row.reaction_counts.0,
+        viewer_reactions: row.viewer_reactions,
+    })
+}
\ No newline at end of file
@@ -0,0 +1,269 @@
+use std::{borrow::Cow, sync::OnceLock};
+
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use syntect::{
+    html::{ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::models::ContentFormat;
+
+/// Class prefix of highlighted code tokens, e.g. `hl-keyword`.
+pub const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+const EXCERPT_LENGTH: usize = 200;
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Everything derived from a post's content, stored alongside it on write.
+pub struct RenderedContent {
+    pub html: String,
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+}
+
+pub fn render_content(format: ContentFormat, content: &str) -> RenderedContent {
+    let (html, text) = match format {
+        ContentFormat::Plain => (render_plain(content), content.to_string()),
+        ContentFormat::Markdown => render_markdown(content),
+    };
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let reading_time_minutes = words.len().div_ceil(WORDS_PER_MINUTE).max(1);
+
+    RenderedContent {
+        html: sanitizer().clean(&html).to_string(),
+        excerpt: excerpt(&words),
+        reading_time_minutes: reading_time_minutes as i32,
+    }
+}
+
+/// Escapes plain text and keeps its paragraphs and line breaks.
+fn render_plain(content: &str) -> String {
+    content
+        .trim()
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph).replace('\n', "<br>")))
+        .collect()
+}
+
+/// Renders CommonMark with highlighted fenced code blocks, returning the
+/// HTML and the document's text for excerpts and word counts.
+fn render_markdown(content: &str) -> (String, String) {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut events = Vec::new();
+    let mut text = String::new();
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or_default().to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::Text(code) if code_block.is_some() => {
+                if let Some((_, source)) = code_block.as_mut() {
+                    source.push_str(&code);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, source)) = code_block.take() {
+                    text.push_str(&source);
+                    text.push(' ');
+                    events.push(Event::Html(highlight_code(&language, &source).into()));
+                }
+            }
+            event => {
+                match &event {
+                    Event::Text(value) | Event::Code(value) => text.push_str(value),
+                    Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+                    _ => {}
+                }
+                events.push(event);
+            }
+        }
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    (output, text)
+}
+
+fn highlight_code(language: &str, source: &str) -> String {
+    let syntax_set = syntax_set();
+    let syntax = syntax_set
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        syntax_set,
+        ClassStyle::SpacedPrefixed {
+            prefix: HIGHLIGHT_CLASS_PREFIX,
+        },
+    );
+    for line in LinesWithEndings::from(source) {
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            // Fall back to unhighlighted code rather than failing the write
+            return code_block_html(language, &escape_html(source));
+        }
+    }
+
+    code_block_html(language, &generator.finalize())
+}
+
+fn code_block_html(language: &str, code: &str) -> String {
+    if language.is_empty() {
+        format!("<pre><code>{}</code></pre>\n", code)
+    } else {
+        format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>\n",
+            escape_html(language),
+            code
+        )
+    }
+}
+
+/// First words of the content, cut at a word boundary.
+fn excerpt(words: &[&str]) -> String {
+    let mut excerpt = String::new();
+
+    for word in words {
+        let separator = usize::from(!excerpt.is_empty());
+        if excerpt.chars().count() + separator + word.chars().count() > EXCERPT_LENGTH {
+            excerpt.push('…');
+            break;
+        }
+        if separator == 1 {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+
+    excerpt
+}
+
+/// Delimiters `ts_headline` is asked to put around matches. They are
+/// stripped from the document beforehand, so only matches carry them.
+pub const HIGHLIGHT_START: char = '\u{1}';
+pub const HIGHLIGHT_STOP: char = '\u{2}';
+
+/// Turns a `ts_headline` result delimited with [`HIGHLIGHT_START`] and
+/// [`HIGHLIGHT_STOP`] into HTML: the text is escaped, since headlines come
+/// from raw `title` and `content`, and matches are wrapped in `<mark>`.
+pub fn highlight_html(headline: &str) -> String {
+    escape_html(headline)
+        .replace(HIGHLIGHT_START, "<mark>")
+        .replace(HIGHLIGHT_STOP, "</mark>")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// HTML sanitizer allowing the markup CommonMark produces, plus the classes
+/// used for code highlighting. Raw HTML in Markdown goes through it too.
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tag_attributes("span", &["class"])
+            .add_tag_attributes("code", &["class"])
+            .add_tag_attributes("input", &["type", "checked", "disabled"])
+            .add_tags(&["input"])
+            .attribute_filter(|element, attribute, value| match (element, attribute) {
+                ("span", "class") | ("code", "class") => {
+                    let classes: Vec<&str> = value
+                        .split_whitespace()
+                        .filter(|class| {
+                            class.starts_with(HIGHLIGHT_CLASS_PREFIX) || class.starts_with("language-")
+                        })
+                        .collect();
+                    Some(Cow::Owned(classes.join(" ")))
+                }
+                ("input", "type") if value != "checkbox" => None,
+                _ => Some(Cow::Borrowed(value)),
+            });
+        builder
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_escape_the_text_and_mark_matches() {
+        let headline = format!("<script>alert(1)</script> & {}cats{}", HIGHLIGHT_START, HIGHLIGHT_STOP);
+
+        assert_eq!(
+            highlight_html(&headline),
+            "&lt;script&gt;alert(1)&lt;/script&gt; &amp; <mark>cats</mark>"
+        );
+    }
+
+    #[test]
+    fn markdown_is_rendered_and_raw_html_is_sanitized() {
+        let rendered = render_content(
+            ContentFormat::Markdown,
+            "# Title\n\n**bold** <script>alert(1)</script><a href=\"javascript:alert(1)\" onclick=\"x()\">link</a>",
+        );
+
+        assert!(rendered.html.contains("<h1>Title</h1>"));
+        assert!(rendered.html.contains("<strong>bold</strong>"));
+        assert!(!rendered.html.contains("<script"));
+        assert!(!rendered.html.contains("javascript:"));
+        assert!(!rendered.html.contains("onclick"));
+    }
+
+    #[test]
+    fn code_blocks_keep_only_highlighting_classes() {
+        let rendered = render_content(ContentFormat::Markdown, "```rust\nfn main() {}\n```");
+
+        assert!(rendered.html.contains("<code class=\"language-rust\">"));
+        assert!(rendered.html.contains("class=\"hl-"));
+
+        let rendered = render_content(ContentFormat::Markdown, "<span class=\"evil hl-keyword\">x</span>");
+        assert!(rendered.html.contains("<span class=\"hl-keyword\">x</span>"));
+    }
+
+    #[test]
+    fn plain_text_is_escaped_into_paragraphs() {
+        let rendered = render_content(ContentFormat::Plain, "one <b>\nline\n\ntwo");
+
+        assert_eq!(rendered.html, "<p>one &lt;b&gt;<br>line</p><p>two</p>");
+        assert_eq!(rendered.excerpt, "one <b> line two");
+        assert_eq!(rendered.reading_time_minutes, 1);
+    }
+
+    #[test]
+    fn excerpts_are_cut_at_a_word_boundary() {
+        let rendered = render_content(ContentFormat::Plain, &"word ".repeat(300));
+
+        assert!(rendered.excerpt.ends_with("word…"));
+        assert!(rendered.excerpt.chars().count() <= EXCERPT_LENGTH + 1);
+        assert_eq!(rendered.reading_time_minutes, 2);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use similar::TextDiff;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::{ContentFormat, PostRevision, RevisionDiffResponse};
+
+/// Stores the given snapshot as the next revision of `post_id`.
+///
+/// Call this in the same transaction as the write to `posts`: the row lock
+/// taken by that write serializes concurrent editors, so revision numbers
+/// stay gapless.
+pub async fn record_revision(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    title: &str,
+    content: &str,
+    content_format: ContentFormat,
+    editor_id: Uuid,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO post_revisions (post_id, revision, title, content, content_format, editor_id)
+        SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5
+        FROM post_revisions
+        WHERE post_id = $1
+        RETURNING revision
+        "#,
+        post_id,
+        title,
+        content,
+        content_format as ContentFormat,
+        editor_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub fn diff_revisions(from: &PostRevision, to: &PostRevision) -> RevisionDiffResponse {
+    let from_header = format!("revision {}", from.revision);
+    let to_header = format!("revision {}", to.revision);
+
+    let unified = |old: &str, new: &str| {
+        if old == new {
+            return String::new();
+        }
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&from_header, &to_header)
+            .to_string()
+    };
+
+    RevisionDiffResponse {
+        from: from.revision,
+        to: to.revision,
+        title_diff: unified(&from.title, &to.title),
+        content_diff: unified(&from.content, &to.content),
+    }
+}
\ No newline at end of file
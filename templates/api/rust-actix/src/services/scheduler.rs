@@ -0,0 +1,99 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use sqlx::PgPool;
+
+use crate::storage::StorageBackend;
+
+use super::{AttachmentService, PostService, ResumableUploadService, ViewCounter};
+
+/// How long a stored file must have gone unused before it is swept.
+const BLOB_SWEEP_GRACE_SECS: i32 = 3600;
+
+/// Periodically flips scheduled posts to published once they are due.
+pub fn spawn_scheduled_publisher(pool: PgPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match PostService::new(&pool).publish_due().await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Published {} scheduled post(s)", count),
+                Err(e) => log::error!("Failed to publish scheduled posts: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Periodically purges posts whose trash retention has expired.
+pub fn spawn_trash_purger(pool: PgPool, interval: Duration, retention_days: i32) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match PostService::new(&pool).purge_trash(retention_days).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Purged {} trashed post(s)", count),
+                Err(e) => log::error!("Failed to purge trashed posts: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Periodically deletes stored files that no attachment refers to anymore,
+/// e.g. after attachments were deleted or their posts purged.
+pub fn spawn_blob_sweeper(pool: PgPool, storage: Arc<dyn StorageBackend>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match AttachmentService::new(&pool, storage.as_ref())
+                .sweep_unused_blobs(BLOB_SWEEP_GRACE_SECS)
+                .await
+            {
+                Ok(0) => {}
+                Ok(count) => log::info!("Swept {} unused attachment file(s)", count),
+                Err(e) => log::error!("Failed to sweep attachment files: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Periodically deletes expired resumable uploads and their partial files.
+pub fn spawn_upload_expirer(pool: PgPool, dir: PathBuf, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match ResumableUploadService::new(&pool, &dir).expire().await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Expired {} resumable upload(s)", count),
+                Err(e) => log::error!("Failed to expire resumable uploads: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Periodically writes buffered post views to the daily totals.
+pub fn spawn_view_flusher(pool: PgPool, views: Arc<ViewCounter>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match views.flush(&pool).await {
+                Ok(0) => {}
+                Ok(count) => log::debug!("Flushed {} post view(s)", count),
+                Err(e) => log::error!("Failed to flush post views: {:?}", e),
+            }
+        }
+    });
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::MAX_SLUG_LENGTH;
+
+/// URL slug for a title: transliterated to ASCII, lowercase and hyphenated,
+/// so `"Crème Brûlée"` becomes `creme-brulee`.
+pub fn slug_for_title(title: &str) -> String {
+    let mut slug = slug::slugify(title);
+
+    if slug.len() > MAX_SLUG_LENGTH {
+        // Cut at a word boundary; slugify only produces ASCII
+        let cut = slug[..=MAX_SLUG_LENGTH].rfind('-').unwrap_or(MAX_SLUG_LENGTH);
+        slug.truncate(cut);
+    }
+
+    if slug.is_empty() {
+        "post".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Reserves `slug` for `post_id`. Slugs the post has used before are still
+/// its own; returns `false` when another post holds the slug.
+pub async fn claim_slug(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    slug: &str,
+) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO post_slugs (slug, post_id)
+        VALUES ($1, $2)
+        ON CONFLICT (slug) DO UPDATE SET post_id = EXCLUDED.post_id
+        WHERE post_slugs.post_id = EXCLUDED.post_id
+        RETURNING slug
+        "#,
+        slug,
+        post_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(claimed.is_some())
+}
+
+/// Reserves the first free slug among `base`, `base-2`, `base-3`, ...
+pub async fn claim_unique_slug(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    base: &str,
+) -> Result<String, sqlx::Error> {
+    let taken: HashSet<String> = sqlx::query_scalar!(
+        r#"
+        SELECT slug FROM post_slugs
+        WHERE (slug = $1 OR slug LIKE $2) AND post_id <> $3
+        "#,
+        base,
+        format!("{}-%", base),
+        post_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut suffix = 1;
+    loop {
+        let candidate = match suffix {
+            1 => base.to_string(),
+            _ => format!("{}-{}", base, suffix),
+        };
+
+        // Another writer may have taken the slug since it was read
+        if !taken.contains(&candidate) && claim_slug(conn, post_id, &candidate).await? {
+            return Ok(candidate);
+        }
+
+        suffix += 1;
+    }
+}
+
+/// Points the post at `slug`, which must already be claimed.
+pub async fn set_current_slug(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    slug: &str,
+    custom: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE posts SET slug = $1, custom_slug = $2 WHERE id = $3",
+        slug,
+        custom,
+        post_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titles_are_transliterated_and_hyphenated() {
+        assert_eq!(slug_for_title("Crème Brûlée"), "creme-brulee");
+        assert_eq!(slug_for_title("  Hello,   World! "), "hello-world");
+    }
+
+    #[test]
+    fn long_titles_are_cut_at_a_word_boundary() {
+        let slug = slug_for_title(&"word ".repeat(40));
+
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("word"));
+    }
+
+    #[test]
+    fn titles_without_letters_fall_back_to_post() {
+        assert_eq!(slug_for_title("!!!"), "post");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::TagCount;
+
+/// Replaces the tags of `post_id` with `tags`, creating any that do not
+/// exist yet. `tags` must already be normalized.
+pub async fn set_post_tags(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO tags (name)
+        SELECT unnest($1::text[])
+        ON CONFLICT (name) DO NOTHING
+        "#,
+        tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM post_tags
+        WHERE post_id = $1
+          AND tag_id NOT IN (SELECT id FROM tags WHERE name = ANY($2))
+        "#,
+        post_id,
+        tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO post_tags (post_id, tag_id)
+        SELECT $1, id FROM tags WHERE name = ANY($2)
+        ON CONFLICT DO NOTHING
+        "#,
+        post_id,
+        tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Names of the tags on `post_id`, sorted.
+pub async fn post_tags(conn: &mut PgConnection, post_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+        WHERE pt.post_id = $1 ORDER BY t.name
+        "#,
+        post_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+pub struct TagService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> TagService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Most used tags across published posts, for tag clouds. Tags that are
+    /// only on drafts or trashed posts are left out.
+    pub async fn list_counts(&self, limit: i64) -> Result<Vec<TagCount>, sqlx::Error> {
+        sqlx::query_as!(
+            TagCount,
+            r#"
+            SELECT t.name, COUNT(*) as "post_count!"
+            FROM tags t
+            JOIN post_tags pt ON pt.tag_id = t.id
+            JOIN posts p ON p.id = pt.post_id
+            WHERE p.status = 'published' AND p.hidden_at IS NULL AND p.deleted_at IS NULL
+            GROUP BY t.name
+            ORDER BY COUNT(*) DESC, t.name
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+}
\ No newline at end of file
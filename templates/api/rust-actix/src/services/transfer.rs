@@ -0,0 +1,229 @@
+use std::borrow::Cow;
+
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::{stream, Stream};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{ContentFormat, ExportFormat, ExportedPost, PostStatus};
+
+const CSV_HEADER: &str =
+    "id,title,slug,content,content_format,locale,status,publish_at,tags,author_email,created_at,updated_at\n";
+
+/// Where an export stream has got to.
+enum ExportState {
+    Start,
+    After(DateTime<Utc>, Uuid),
+    Done,
+}
+
+/// Streams the posts of `owner` (or every post, for `None`) oldest first,
+/// as one NDJSON line or CSV row each. Rows are read `batch_size` at a time
+/// with keyset pagination, so neither the result nor a connection is held
+/// for the whole download. Trashed posts are left out.
+pub fn export_posts(
+    pool: PgPool,
+    owner: Option<Uuid>,
+    format: ExportFormat,
+    batch_size: i64,
+) -> impl Stream<Item = Result<Bytes, sqlx::Error>> {
+    stream::try_unfold(ExportState::Start, move |state| {
+        let pool = pool.clone();
+        async move {
+            let after = match state {
+                ExportState::Done => return Ok(None),
+                ExportState::Start => None,
+                ExportState::After(created_at, id) => Some((created_at, id)),
+            };
+
+            let posts = export_batch(&pool, owner, after, batch_size).await?;
+
+            let mut chunk = String::new();
+            if after.is_none() && format == ExportFormat::Csv {
+                chunk.push_str(CSV_HEADER);
+            }
+            for post in &posts {
+                match format {
+                    ExportFormat::Ndjson => {
+                        chunk.push_str(&serde_json::to_string(post).unwrap_or_default());
+                        chunk.push('\n');
+                    }
+                    ExportFormat::Csv => push_csv_row(&mut chunk, post),
+                }
+            }
+
+            let next = match posts.last() {
+                Some(post) if posts.len() as i64 == batch_size => ExportState::After(post.created_at, post.id),
+                _ => ExportState::Done,
+            };
+            if chunk.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some((Bytes::from(chunk), next)))
+        }
+    })
+}
+
+async fn export_batch(
+    pool: &PgPool,
+    owner: Option<Uuid>,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<ExportedPost>, sqlx::Error> {
+    let (after_created_at, after_id) = after.unzip();
+
+    sqlx::query_as!(
+        ExportedPost,
+        r#"
+        SELECT p.id, p.title, p.slug, p.content, p.content_format as "content_format: ContentFormat",
+               p.locale, p.status as "status: PostStatus", p.publish_at,
+               ARRAY(
+                   SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                   WHERE pt.post_id = p.id ORDER BY t.name
+               ) as "tags!",
+               u.email as author_email, p.created_at, p.updated_at
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.deleted_at IS NULL
+          AND ($1::uuid IS NULL OR p.user_id = $1)
+          AND ($2::timestamptz IS NULL OR (p.created_at, p.id) > ($2, $3))
+        ORDER BY p.created_at, p.id
+        LIMIT $4
+        "#,
+        owner,
+        after_created_at,
+        after_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+fn push_csv_row(out: &mut String, post: &ExportedPost) {
+    let fields = [
+        post.id.to_string(),
+        post.title.clone(),
+        post.slug.clone(),
+        post.content.clone(),
+        post.content_format.as_str().to_string(),
+        post.locale.clone(),
+        post.status.as_str().to_string(),
+        post.publish_at.map(timestamp).unwrap_or_default(),
+        post.tags.join(","),
+        post.author_email.clone(),
+        timestamp(post.created_at),
+        timestamp(post.updated_at),
+    ];
+
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        push_csv_field(out, field);
+    }
+    out.push('\n');
+}
+
+/// Formats timestamps like the NDJSON export does.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Quotes a field (RFC 4180) when it contains a delimiter, quote or line break.
+/// Fields that spreadsheets would run as formulas get a leading `'`.
+fn push_csv_field(out: &mut String, field: &str) {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", field))
+    } else {
+        Cow::Borrowed(field)
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(&field);
+    }
+}
+
+/// Keeps the original timestamps of an imported post: when it was created
+/// and, for published posts, when it was published.
+pub async fn restore_timestamps(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    created_at: Option<DateTime<Utc>>,
+    published_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE posts
+        SET created_at = COALESCE($2, created_at),
+            publish_at = CASE WHEN status = 'published' THEN COALESCE($3, publish_at) ELSE publish_at END
+        WHERE id = $1
+        "#,
+        post_id,
+        created_at,
+        published_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub struct TransferService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> TransferService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn is_admin(&self, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let is_admin = sqlx::query_scalar!(
+            "SELECT is_admin FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(is_admin.unwrap_or(false))
+    }
+
+    pub async fn find_user_by_email(&self, email: &str) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+            .fetch_optional(self.pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_field(field: &str) -> String {
+        let mut out = String::new();
+        push_csv_field(&mut out, field);
+        out
+    }
+
+    #[test]
+    fn fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn formula_like_fields_are_defused() {
+        assert_eq!(csv_field("=HYPERLINK(\"http://x\")"), "\"'=HYPERLINK(\"\"http://x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("a = b"), "a = b");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{ContentFormat, PostTranslation, PostTranslations};
+
+use super::{render_content, RenderedContent};
+
+/// Adds or replaces the `locale` translation of `post_id` and bumps the
+/// post's `updated_at`, which its cache validators derive from. Returns the
+/// translation and whether it is new.
+pub async fn save_translation(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    locale: &str,
+    title: &str,
+    content: &str,
+    rendered: &RenderedContent,
+) -> Result<(PostTranslation, bool), sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO post_translations (
+            post_id, locale, title, content, content_html, excerpt, reading_time_minutes
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (post_id, locale) DO UPDATE SET
+            title = EXCLUDED.title, content = EXCLUDED.content, content_html = EXCLUDED.content_html,
+            excerpt = EXCLUDED.excerpt, reading_time_minutes = EXCLUDED.reading_time_minutes,
+            updated_at = NOW()
+        RETURNING post_id, locale, title, content, content_html, excerpt, reading_time_minutes,
+                  created_at, updated_at, xmax = 0 as "created!"
+        "#,
+        post_id,
+        locale,
+        title,
+        content,
+        rendered.html,
+        rendered.excerpt,
+        rendered.reading_time_minutes
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    touch_post(conn, post_id).await?;
+
+    let translation = PostTranslation {
+        post_id: row.post_id,
+        locale: row.locale,
+        title: row.title,
+        content: row.content,
+        content_html: row.content_html,
+        excerpt: row.excerpt,
+        reading_time_minutes: row.reading_time_minutes,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    };
+    Ok((translation, row.created))
+}
+
+/// Deletes the `locale` translation of `post_id`; returns `false` if there
+/// was none.
+pub async fn delete_translation(conn: &mut PgConnection, post_id: Uuid, locale: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM post_translations WHERE post_id = $1 AND locale = $2",
+        post_id,
+        locale
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+    touch_post(conn, post_id).await?;
+    Ok(true)
+}
+
+/// Renders the translations of `post_id` again in `format`. Callers changing
+/// a post's content format must call it in the same transaction.
+pub async fn rerender_translations(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    format: ContentFormat,
+) -> Result<(), sqlx::Error> {
+    let translations = sqlx::query!(
+        "SELECT locale, content FROM post_translations WHERE post_id = $1 FOR UPDATE",
+        post_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for translation in translations {
+        let rendered = render_content(format, &translation.content);
+        sqlx::query!(
+            r#"
+            UPDATE post_translations
+            SET content_html = $3, excerpt = $4, reading_time_minutes = $5, updated_at = NOW()
+            WHERE post_id = $1 AND locale = $2
+            "#,
+            post_id,
+            translation.locale,
+            rendered.html,
+            rendered.excerpt,
+            rendered.reading_time_minutes
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn touch_post(conn: &mut PgConnection, post_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!("UPDATE posts SET updated_at = NOW() WHERE id = $1", post_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+pub struct TranslationService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> TranslationService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Translations of `post_id`, by locale.
+    pub async fn list(&self, post_id: Uuid) -> Result<Vec<PostTranslation>, sqlx::Error> {
+        sqlx::query_as!(
+            PostTranslation,
+            r#"
+            SELECT post_id, locale, title, content, content_html, excerpt, reading_time_minutes,
+                   created_at, updated_at
+            FROM post_translations
+            WHERE post_id = $1
+            ORDER BY locale
+            "#,
+            post_id
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    pub async fn find(&self, post_id: Uuid, locale: &str) -> Result<Option<PostTranslation>, sqlx::Error> {
+        sqlx::query_as!(
+            PostTranslation,
+            r#"
+            SELECT post_id, locale, title, content, content_html, excerpt, reading_time_minutes,
+                   created_at, updated_at
+            FROM post_translations
+            WHERE post_id = $1 AND locale = $2
+            "#,
+            post_id,
+            locale
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Fetches one translation per post of `wanted`, given as
+    /// `(post_id, locale)` pairs.
+    pub async fn find_many(&self, wanted: &[(Uuid, String)]) -> Result<PostTranslations, sqlx::Error> {
+        if wanted.is_empty() {
+            return Ok(PostTranslations::new());
+        }
+        let (post_ids, locales): (Vec<Uuid>, Vec<String>) = wanted.iter().cloned().unzip();
+
+        let translations = sqlx::query_as!(
+            PostTranslation,
+            r#"
+            SELECT t.post_id, t.locale, t.title, t.content, t.content_html, t.excerpt,
+                   t.reading_time_minutes, t.created_at, t.updated_at
+            FROM post_translations t
+            JOIN UNNEST($1::uuid[], $2::varchar[]) AS w(post_id, locale)
+              ON w.post_id = t.post_id AND w.locale = t.locale
+            "#,
+            &post_ids,
+            &locales
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(translations
+            .into_iter()
+            .map(|translation| (translation.post_id, translation))
+            .collect())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,278 @@
+use std::{
+    collections::HashSet,
+    io::{ErrorKind, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use sqlx::PgPool;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::models::ResumableUpload;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResumableUploadError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Uploads currently receiving data. Partial files live on this server's
+/// disk, so an in-process lock is enough to keep writers apart.
+#[derive(Default)]
+pub struct UploadLocks {
+    active: Mutex<HashSet<Uuid>>,
+}
+
+/// Held while an upload is being written; releases it on drop.
+pub struct UploadLock<'a> {
+    locks: &'a UploadLocks,
+    id: Uuid,
+}
+
+impl UploadLocks {
+    /// Locks an upload, or returns `None` if another request holds it.
+    pub fn try_lock(&self, id: Uuid) -> Option<UploadLock<'_>> {
+        let mut active = self.active.lock().unwrap_or_else(PoisonError::into_inner);
+        active.insert(id).then_some(UploadLock { locks: self, id })
+    }
+}
+
+impl Drop for UploadLock<'_> {
+    fn drop(&mut self) {
+        let mut active = self.locks.active.lock().unwrap_or_else(PoisonError::into_inner);
+        active.remove(&self.id);
+    }
+}
+
+pub struct NewResumableUpload {
+    pub post_id: Uuid,
+    pub filename: String,
+    pub declared_type: Option<String>,
+    pub private: bool,
+    pub metadata: String,
+    pub length: i64,
+}
+
+/// Result of writing one request body to an upload.
+pub struct ChunkOutcome {
+    pub offset: i64,
+    pub expires_at: DateTime<Utc>,
+    /// The body held more bytes than the upload had left, so none of it
+    /// was recorded.
+    pub overflowed: bool,
+    /// The body broke off, e.g. because the client went away. What arrived
+    /// until then is kept.
+    pub interrupted: bool,
+}
+
+pub struct ResumableUploadService<'a> {
+    pool: &'a PgPool,
+    dir: &'a Path,
+}
+
+impl<'a> ResumableUploadService<'a> {
+    pub fn new(pool: &'a PgPool, dir: &'a Path) -> Self {
+        Self { pool, dir }
+    }
+
+    fn path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(id.to_string())
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        upload: NewResumableUpload,
+        expires_in_secs: i64,
+    ) -> Result<ResumableUpload, ResumableUploadError> {
+        let upload = sqlx::query_as!(
+            ResumableUpload,
+            r#"
+            INSERT INTO resumable_uploads (
+                user_id, post_id, filename, declared_type, private, metadata, upload_length, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW() + make_interval(secs => $8))
+            RETURNING
+                id, user_id, post_id, filename, declared_type, private, metadata,
+                upload_length, upload_offset, attachment_id, completed_at, expires_at
+            "#,
+            user_id,
+            upload.post_id,
+            upload.filename,
+            upload.declared_type,
+            upload.private,
+            upload.metadata,
+            upload.length,
+            expires_in_secs as f64
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        // The row comes first so that the expirer never sees a file without one
+        if let Err(e) = tokio::fs::File::create(self.path(upload.id)).await {
+            sqlx::query!("DELETE FROM resumable_uploads WHERE id = $1", upload.id)
+                .execute(self.pool)
+                .await?;
+            return Err(e.into());
+        }
+
+        Ok(upload)
+    }
+
+    pub async fn find(&self, id: Uuid, user_id: Uuid) -> Result<Option<ResumableUpload>, sqlx::Error> {
+        sqlx::query_as!(
+            ResumableUpload,
+            r#"
+            SELECT
+                id, user_id, post_id, filename, declared_type, private, metadata,
+                upload_length, upload_offset, attachment_id, completed_at, expires_at
+            FROM resumable_uploads
+            WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Appends a request body at the upload's current offset and records
+    /// how much of it reached the disk, extending the upload's expiry.
+    pub async fn write_chunk<S, E>(
+        &self,
+        upload: &ResumableUpload,
+        mut body: S,
+        expires_in_secs: i64,
+    ) -> Result<ChunkOutcome, ResumableUploadError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Unpin,
+    {
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(self.path(upload.id))
+            .await?;
+        // Drop anything an earlier request wrote but never recorded
+        file.set_len(upload.upload_offset as u64).await?;
+        file.seek(SeekFrom::Start(upload.upload_offset as u64)).await?;
+
+        let mut remaining = (upload.upload_length - upload.upload_offset) as usize;
+        let mut written = 0;
+        let mut interrupted = false;
+
+        while let Some(chunk) = body.next().await {
+            let Ok(chunk) = chunk else {
+                interrupted = true;
+                break;
+            };
+
+            if chunk.len() > remaining {
+                // The next write truncates whatever landed on disk
+                return Ok(ChunkOutcome {
+                    offset: upload.upload_offset,
+                    expires_at: upload.expires_at,
+                    overflowed: true,
+                    interrupted: false,
+                });
+            }
+
+            file.write_all(&chunk).await?;
+            written += chunk.len();
+            remaining -= chunk.len();
+        }
+
+        // Only count bytes that are safely on disk
+        file.sync_data().await?;
+
+        let offset = upload.upload_offset + written as i64;
+        let expires_at = sqlx::query_scalar!(
+            r#"
+            UPDATE resumable_uploads
+            SET upload_offset = $2, expires_at = NOW() + make_interval(secs => $3)
+            WHERE id = $1
+            RETURNING expires_at
+            "#,
+            upload.id,
+            offset,
+            expires_in_secs as f64
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(ChunkOutcome {
+            offset,
+            expires_at,
+            overflowed: false,
+            interrupted,
+        })
+    }
+
+    pub async fn read_file(&self, upload: &ResumableUpload) -> Result<Bytes, std::io::Error> {
+        tokio::fs::read(self.path(upload.id)).await.map(Bytes::from)
+    }
+
+    /// Marks an upload as handed over to attachment storage and drops its
+    /// partial file. The record stays until it expires so that clients can
+    /// still look it up.
+    pub async fn complete(&self, id: Uuid, attachment_id: Uuid) -> Result<(), ResumableUploadError> {
+        sqlx::query!(
+            "UPDATE resumable_uploads SET completed_at = NOW(), attachment_id = $2 WHERE id = $1",
+            id,
+            attachment_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        self.remove_file(id).await
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), ResumableUploadError> {
+        sqlx::query!("DELETE FROM resumable_uploads WHERE id = $1", id)
+            .execute(self.pool)
+            .await?;
+
+        self.remove_file(id).await
+    }
+
+    /// Deletes expired uploads, and partial files whose upload went away
+    /// with its post or user.
+    pub async fn expire(&self) -> Result<u64, ResumableUploadError> {
+        let expired = sqlx::query_scalar!("DELETE FROM resumable_uploads WHERE expires_at <= NOW() RETURNING id")
+            .fetch_all(self.pool)
+            .await?;
+        for id in &expired {
+            self.remove_file(*id).await?;
+        }
+
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(id) = entry.file_name().to_str().and_then(|name| Uuid::parse_str(name).ok()) {
+                files.push(id);
+            }
+        }
+
+        let known: HashSet<Uuid> = sqlx::query_scalar!("SELECT id FROM resumable_uploads WHERE id = ANY($1)", &files)
+            .fetch_all(self.pool)
+            .await?
+            .into_iter()
+            .collect();
+        for id in files.into_iter().filter(|id| !known.contains(id)) {
+            self.remove_file(id).await?;
+        }
+
+        Ok(expired.len() as u64)
+    }
+
+    async fn remove_file(&self, id: Uuid) -> Result<(), ResumableUploadError> {
+        match tokio::fs::remove_file(self.path(id)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{DailyViews, PostStats};
+
+/// Who viewed a post, for deduplicating repeat views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Viewer {
+    User(Uuid),
+    /// Anonymous readers, told apart by a hash of their address and user
+    /// agent so neither is kept in memory.
+    Anonymous(u64),
+}
+
+impl Viewer {
+    pub fn anonymous(address: &str, user_agent: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        (address, user_agent).hash(&mut hasher);
+        Self::Anonymous(hasher.finish())
+    }
+}
+
+/// Counts post views in memory so reads never write to the database; a
+/// background task adds them to the daily totals with [`ViewCounter::flush`].
+/// Views buffered when the process dies are lost.
+pub struct ViewCounter {
+    dedup_window: Duration,
+    state: Mutex<ViewState>,
+}
+
+#[derive(Default)]
+struct ViewState {
+    /// When each viewer's last counted view of each post happened.
+    seen: HashMap<(Uuid, Viewer), Instant>,
+    /// Views not written yet, per post and UTC day.
+    pending: HashMap<(Uuid, NaiveDate), i64>,
+}
+
+impl ViewCounter {
+    pub fn new(dedup_window: Duration) -> Self {
+        Self {
+            dedup_window,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Counts a view of `post_id`, unless `viewer` already had one counted
+    /// within the dedup window.
+    pub fn record(&self, post_id: Uuid, viewer: Viewer) {
+        let now = Instant::now();
+        let mut state = self.lock();
+
+        if let Some(last) = state.seen.get(&(post_id, viewer)) {
+            if now.duration_since(*last) < self.dedup_window {
+                return;
+            }
+        }
+        state.seen.insert((post_id, viewer), now);
+        *state.pending.entry((post_id, Utc::now().date_naive())).or_default() += 1;
+    }
+
+    /// Adds the buffered views to the daily totals and forgets viewers whose
+    /// window has passed. Returns the number of views written; if the write
+    /// fails they are kept for the next flush.
+    pub async fn flush(&self, pool: &PgPool) -> Result<i64, sqlx::Error> {
+        let pending = {
+            let mut state = self.lock();
+            let window = self.dedup_window;
+            state.seen.retain(|_, last| last.elapsed() < window);
+            std::mem::take(&mut state.pending)
+        };
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let mut post_ids = Vec::with_capacity(pending.len());
+        let mut days = Vec::with_capacity(pending.len());
+        let mut views = Vec::with_capacity(pending.len());
+        for (&(post_id, day), &count) in &pending {
+            post_ids.push(post_id);
+            days.push(day);
+            views.push(count);
+        }
+
+        // Views of posts purged since they were counted are dropped
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO post_view_daily (post_id, day, views)
+            SELECT v.post_id, v.day, v.views
+            FROM UNNEST($1::uuid[], $2::date[], $3::bigint[]) AS v(post_id, day, views)
+            WHERE EXISTS (SELECT 1 FROM posts p WHERE p.id = v.post_id)
+            ON CONFLICT (post_id, day)
+            DO UPDATE SET views = post_view_daily.views + EXCLUDED.views
+            "#,
+            &post_ids,
+            &days,
+            &views
+        )
+        .execute(pool)
+        .await;
+
+        if let Err(e) = result {
+            let mut state = self.lock();
+            for (key, count) in pending {
+                *state.pending.entry(key).or_default() += count;
+            }
+            return Err(e);
+        }
+
+        Ok(views.iter().sum())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ViewState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub struct ViewService<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> ViewService<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Flushed views of `post_id`, day by day from `from` to `to` inclusive.
+    pub async fn stats(
+        &self,
+        post_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<PostStats, sqlx::Error> {
+        let total_views = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(views), 0)::BIGINT as "total!" FROM post_view_daily WHERE post_id = $1"#,
+            post_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        let daily = sqlx::query_as!(
+            DailyViews,
+            r#"
+            SELECT d.day::DATE as "date!", COALESCE(v.views, 0) as "views!"
+            FROM generate_series($2::DATE, $3::DATE, INTERVAL '1 day') AS d(day)
+            LEFT JOIN post_view_daily v ON v.post_id = $1 AND v.day = d.day::DATE
+            ORDER BY d.day
+            "#,
+            post_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(PostStats {
+            post_id,
+            total_views,
+            from,
+            to,
+            daily,
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use uuid::Uuid;
+
+use super::{StorageBackend, StorageError};
+
+/// Stores objects as files below a root directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub async fn new(root: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let root = root.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&root).await?;
+        Ok(Self { root })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        // Keys come from the application, but never let one escape the root
+        if key.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return Err(StorageError::Backend(format!("invalid storage key '{}'", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a temporary file first so readers never see partial objects
+        let temp = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&temp, &data).await?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(data.into()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::NotFound(key.to_string())),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(tokio::fs::try_exists(self.path(key)?).await?)
+    }
+}
\ No newline at end of file
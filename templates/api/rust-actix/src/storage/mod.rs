@@ -0,0 +1,100 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::config::Config;
+
+pub mod local;
+pub mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("object not found: {0}")]
+    NotFound(String),
+    #[error("storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("storage backend error: {0}")]
+    Backend(String),
+}
+
+/// Object store for uploaded files. Keys are `/`-separated paths chosen by
+/// the application, e.g. `blobs/ab/ab12...`.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Stores `data` under `key`, replacing any existing object.
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError>;
+
+    /// Removes `key`; deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError>;
+
+    /// A URL clients can download `key` from directly for `expires_in`, for
+    /// backends that serve files themselves. `None` means downloads have to
+    /// go through the API.
+    async fn presigned_url(
+        &self,
+        _key: &str,
+        _filename: &str,
+        _expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+}
+
+/// `Content-Disposition` value showing a file inline under its original
+/// name, with an ASCII fallback for old clients (RFC 6266).
+pub fn inline_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' { c } else { '_' })
+        .collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+
+    format!("inline; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+/// Builds the backend selected by `STORAGE_BACKEND`.
+pub async fn from_config(config: &Config) -> Result<Arc<dyn StorageBackend>, StorageError> {
+    match config.storage_backend.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(&config.storage_local_path).await?)),
+        "s3" => Ok(Arc::new(S3Storage::new(config).await?)),
+        other => Err(StorageError::Backend(format!(
+            "unknown storage backend '{}', expected 'local' or 's3'",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_names_are_kept() {
+        assert_eq!(
+            inline_disposition("report-2024.pdf"),
+            "inline; filename=\"report-2024.pdf\"; filename*=UTF-8''report-2024.pdf"
+        );
+    }
+
+    #[test]
+    fn other_names_get_an_ascii_fallback_and_are_percent_encoded() {
+        assert_eq!(
+            inline_disposition("Über \"plan\".png"),
+            "inline; filename=\"_ber__plan_.png\"; filename*=UTF-8''%C3%9Cber%20%22plan%22.png"
+        );
+    }
+}
\ No newline at end of file
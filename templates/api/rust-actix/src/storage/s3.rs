@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    config::{Credentials, Region},
+    error::DisplayErrorContext,
+    presigning::PresigningConfig,
+    primitives::ByteStream,
+    Client,
+};
+use bytes::Bytes;
+
+use crate::config::Config;
+
+use super::{inline_disposition, StorageBackend, StorageError};
+
+/// Stores objects in an S3 bucket. Works with S3-compatible services such as
+/// MinIO by setting `S3_ENDPOINT` and `S3_FORCE_PATH_STYLE=true`.
+pub struct S3Storage {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    pub async fn new(config: &Config) -> Result<Self, StorageError> {
+        let bucket = config
+            .s3_bucket
+            .clone()
+            .ok_or_else(|| StorageError::Backend("S3_BUCKET must be set".to_string()))?;
+
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(Region::new(config.s3_region.clone()));
+        // Without explicit keys the usual AWS credential chain applies
+        if let (Some(access_key), Some(secret_key)) =
+            (&config.s3_access_key_id, &config.s3_secret_access_key)
+        {
+            loader = loader.credentials_provider(Credentials::new(
+                access_key.clone(),
+                secret_key.clone(),
+                None,
+                None,
+                "config",
+            ));
+        }
+        let shared_config = loader.load().await;
+
+        let mut builder = aws_sdk_s3::config::Builder::from(&shared_config)
+            .force_path_style(config.s3_force_path_style);
+        if let Some(endpoint) = &config.s3_endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+
+        Ok(Self {
+            client: Client::from_conf(builder.build()),
+            bucket,
+        })
+    }
+}
+
+fn backend_error(e: impl std::error::Error) -> StorageError {
+    StorageError::Backend(DisplayErrorContext(e).to_string())
+}
+
+#[async_trait]
+impl StorageBackend for S3Storage {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError> {
+        let object = match self.client.get_object().bucket(&self.bucket).key(key).send().await {
+            Ok(object) => object,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                return Err(StorageError::NotFound(key.to_string()));
+            }
+            Err(e) => return Err(backend_error(e)),
+        };
+
+        let data = object.body.collect().await.map_err(backend_error)?;
+        Ok(data.into_bytes())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        match self.client.head_object().bucket(&self.bucket).key(key).send().await {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(backend_error(e)),
+        }
+    }
+
+    async fn presigned_url(
+        &self,
+        key: &str,
+        filename: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        let presigning = PresigningConfig::expires_in(expires_in).map_err(backend_error)?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .response_content_disposition(inline_disposition(filename))
+            .presigned(presigning)
+            .await
+            .map_err(backend_error)?;
+
+        Ok(Some(request.uri().to_string()))
+    }
+}
\ No newline at end of file